@@ -30,9 +30,7 @@ fn main() {
     let dim = 3;
     let n = 4;
 
-    let mut iter = Permutation::new( dim, n );
-
-    while let Some(item) = iter.next() {
+    for item in Permutation::new( dim, n ) {
         println!("{:?}", item);
     }
 
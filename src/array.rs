@@ -0,0 +1,360 @@
+//! Fixed-size versions of the iterators, which yield `[usize; K]` without allocation.
+//!
+//! The items are `Copy` and can be used as keys of a `HashMap` as they are.
+//! They are generated in the same order as the `Vec`-based iterators.
+//!
+//! ```
+//! use enumcombinatorics::array;
+//!
+//! let mut iter = array::Combination::<2>::new( 3 );
+//!
+//! assert_eq!( iter.next(), Some([0,1]) );
+//! assert_eq!( iter.next(), Some([0,2]) );
+//! assert_eq!( iter.next(), Some([1,2]) );
+//! assert_eq!( iter.next(), None );
+//! ```
+
+
+use crate::count;
+use crate::successor::{permutation, sequence, combination, multichoose};
+use core::array;
+use core::iter::{Iterator, DoubleEndedIterator};
+
+
+/// Both ends of an iterator over `[usize; K]`.
+///
+/// The ends stop when they meet, which is found by comparing them, so this works even if
+/// the number of items does not fit in `u128`.
+#[derive(Debug, Clone)]
+struct Ends<const K: usize> {
+    front: [usize; K],
+    back: [usize; K],
+    front_started: bool,
+    back_started: bool,
+    /// Number of items not yet generated, or `None` if it does not fit in `u128`.
+    rest: Option<u128>,
+}
+
+impl<const K: usize> Ends<K> {
+    fn new( first: [usize; K], total: Option<u128> ) -> Self {
+        Ends { front: first, back: first, front_started: false, back_started: false, rest: total }
+    }
+
+    fn next<F>( &mut self, next: F ) -> Option<[usize; K]>
+        where F: FnOnce(&mut [usize]) -> bool
+    {
+        if self.rest == Some(0) {
+            return None;
+        }
+        if self.front_started && !next(&mut self.front) {
+            self.rest = Some(0);
+            return None;
+        }
+        // 後ろから取り出したものに追いついた
+        if self.back_started && self.front == self.back {
+            self.rest = Some(0);
+            return None;
+        }
+
+        self.front_started = true;
+        self.rest = self.rest.map(|rest| rest - 1);
+        Some(self.front)
+    }
+
+    fn next_back<L, F>( &mut self, last: L, prev: F ) -> Option<[usize; K]>
+        where L: FnOnce() -> [usize; K], F: FnOnce(&mut [usize]) -> bool
+    {
+        if self.rest == Some(0) {
+            return None;
+        }
+        if !self.back_started {
+            self.back = last();
+        } else if !prev(&mut self.back) {
+            self.rest = Some(0);
+            return None;
+        }
+        if self.front_started && self.back == self.front {
+            self.rest = Some(0);
+            return None;
+        }
+
+        self.back_started = true;
+        self.rest = self.rest.map(|rest| rest - 1);
+        Some(self.back)
+    }
+}
+
+
+/// Same as `crate::Permutation` with `dim == K`.
+///
+/// `crate::Permutation` keeps the `n - K` unused numbers sorted after the permutation, but they
+/// do not fit in a fixed-size array. This looks for them among the used ones instead,
+/// so a step takes `O(K^2)` time.
+#[derive(Debug, Clone)]
+pub struct Permutation<const K: usize> {
+    ends: Ends<K>,
+    n: usize,
+}
+
+impl<const K: usize> Permutation<K> {
+    /// Generates all arrangements of `K` distinct numbers out of `0..n`.
+    pub fn new( n: usize ) -> Self {
+        Permutation { ends: Ends::new( array::from_fn(|i| i), count::falling_factorial_u128( n, K ) ), n }
+    }
+
+    /// Number of all permutations, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::falling_factorial_u128( self.n, K )
+    }
+
+    /// Number of permutations not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.ends.rest
+    }
+}
+
+impl<const K: usize> Iterator for Permutation<K> {
+    type Item = [usize; K];
+
+    fn next(&mut self) -> Option<[usize; K]> {
+        let n = self.n;
+        self.ends.next(|state| permutation::shift_partial( state, n, true ))
+    }
+
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        count::size_hint( self.ends.rest )
+    }
+}
+
+impl<const K: usize> DoubleEndedIterator for Permutation<K> {
+    fn next_back(&mut self) -> Option<[usize; K]> {
+        let n = self.n;
+        self.ends.next_back(|| array::from_fn(|i| n - 1 - i), |state| permutation::shift_partial( state, n, false ))
+    }
+}
+
+
+/// Same as `crate::Sequence` with `dim == K`.
+#[derive(Debug, Clone)]
+pub struct Sequence<const K: usize> {
+    ends: Ends<K>,
+    n: usize,
+}
+
+impl<const K: usize> Sequence<K> {
+    /// Generates all sequences of `K` numbers out of `0..n`.
+    pub fn new( n: usize ) -> Self {
+        Sequence { ends: Ends::new( [0; K], count::power_u128( n, K ) ), n }
+    }
+
+    /// Number of all sequences, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::power_u128( self.n, K )
+    }
+
+    /// Number of sequences not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.ends.rest
+    }
+}
+
+impl<const K: usize> Iterator for Sequence<K> {
+    type Item = [usize; K];
+
+    fn next(&mut self) -> Option<[usize; K]> {
+        let radices = [self.n; K];
+        self.ends.next(|state| sequence::next_state( state, &radices ))
+    }
+
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        count::size_hint( self.ends.rest )
+    }
+}
+
+impl<const K: usize> DoubleEndedIterator for Sequence<K> {
+    fn next_back(&mut self) -> Option<[usize; K]> {
+        let (n, radices) = (self.n, [self.n; K]);
+        // n == 0 なら K == 0 か空で、中身は使われない
+        self.ends.next_back(|| [n.saturating_sub(1); K], |state| sequence::prev_state( state, &radices ))
+    }
+}
+
+
+/// Same as `crate::Combination` with `dim == K`.
+#[derive(Debug, Clone)]
+pub struct Combination<const K: usize> {
+    ends: Ends<K>,
+    n: usize,
+}
+
+impl<const K: usize> Combination<K> {
+    /// Generates all increasing sequences of `K` numbers out of `0..n`.
+    pub fn new( n: usize ) -> Self {
+        Combination { ends: Ends::new( array::from_fn(|i| i), count::binomial_u128( n, K ) ), n }
+    }
+
+    /// Number of all combinations, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::binomial_u128( self.n, K )
+    }
+
+    /// Number of combinations not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.ends.rest
+    }
+}
+
+impl<const K: usize> Iterator for Combination<K> {
+    type Item = [usize; K];
+
+    fn next(&mut self) -> Option<[usize; K]> {
+        let n = self.n;
+        self.ends.next(|state| combination::next_state( state, n ))
+    }
+
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        count::size_hint( self.ends.rest )
+    }
+}
+
+impl<const K: usize> DoubleEndedIterator for Combination<K> {
+    fn next_back(&mut self) -> Option<[usize; K]> {
+        let n = self.n;
+        self.ends.next_back(|| array::from_fn(|i| n - K + i), |state| combination::prev_state( state, n ))
+    }
+}
+
+
+/// Same as `crate::Multichoose` with `dim == K`.
+#[derive(Debug, Clone)]
+pub struct Multichoose<const K: usize> {
+    ends: Ends<K>,
+    n: usize,
+}
+
+impl<const K: usize> Multichoose<K> {
+    /// Generates all non-decreasing sequences of `K` numbers out of `0..n`.
+    pub fn new( n: usize ) -> Self {
+        Multichoose { ends: Ends::new( [0; K], count::multichoose_u128( n, K ) ), n }
+    }
+
+    /// Number of all multisets, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::multichoose_u128( self.n, K )
+    }
+
+    /// Number of multisets not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.ends.rest
+    }
+}
+
+impl<const K: usize> Iterator for Multichoose<K> {
+    type Item = [usize; K];
+
+    fn next(&mut self) -> Option<[usize; K]> {
+        let n = self.n;
+        self.ends.next(|state| multichoose::next_state( state, n ))
+    }
+
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        count::size_hint( self.ends.rest )
+    }
+}
+
+impl<const K: usize> DoubleEndedIterator for Multichoose<K> {
+    fn next_back(&mut self) -> Option<[usize; K]> {
+        let n = self.n;
+        self.ends.next_back(|| [n.saturating_sub(1); K], |state| multichoose::prev_state( state, n ))
+    }
+}
+
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::array;
+
+    /// Checks that `iter` generates the same items as `expected`, from both ends.
+    fn check<I, const K: usize>( iter: I, expected: Vec<Vec<usize>> )
+        where I: DoubleEndedIterator<Item = [usize; K]> + Clone
+    {
+        assert_eq!( iter.size_hint(), (expected.len(), Some(expected.len())) );
+        let all: Vec<Vec<usize>> = iter.clone().map(|x| x.to_vec()).collect();
+        assert_eq!( all, expected );
+        let mut all: Vec<Vec<usize>> = iter.clone().rev().map(|x| x.to_vec()).collect();
+        all.reverse();
+        assert_eq!( all, expected );
+
+        // 両端から交互に取り出す
+        let mut iter = iter;
+        let (mut front, mut back) = (Vec::new(), Vec::new());
+        while let Some(x) = iter.next() {
+            front.push(x.to_vec());
+            if let Some(y) = iter.next_back() {
+                back.push(y.to_vec());
+            }
+        }
+        back.reverse();
+        front.extend(back);
+        assert_eq!( front, expected );
+    }
+
+    #[test]
+    fn same_as_vec() {
+        for n in 0..6 {
+            check( array::Permutation::<3>::new( n ), crate::Permutation::new( 3, n ).collect() );
+            check( array::Sequence::<3>::new( n ), crate::Sequence::new( 3, n ).collect() );
+            check( array::Combination::<3>::new( n ), crate::Combination::new( 3, n ).collect() );
+            check( array::Multichoose::<3>::new( n ), crate::Multichoose::new( 3, n ).collect() );
+            check( array::Combination::<0>::new( n ), crate::Combination::new( 0, n ).collect() );
+            check( array::Permutation::<1>::new( n ), crate::Permutation::new( 1, n ).collect() );
+            check( array::Permutation::<4>::new( n ), crate::Permutation::new( 4, n ).collect() );
+        }
+
+        // usize に収まらない
+        let iter = array::Sequence::<30>::new( 10 );
+        assert_eq!( iter.remaining(), Some(10u128.pow(30)) );
+        assert_eq!( iter.size_hint(), (usize::MAX, None) );
+        let m = usize::MAX as u128;
+        assert_eq!( array::Multichoose::<2>::new( usize::MAX ).total(), Some(m * (m + 1) / 2) );
+    }
+
+    #[test]
+    fn meet_without_count() {
+        // 総数が分からないことにして 0..n を両端から取り出す
+        for n in 1..7 {
+            let next = |s: &mut [usize]| { s[0] += 1; s[0] < n };
+            let prev = |s: &mut [usize]| if s[0] > 0 { s[0] -= 1; true } else { false };
+
+            let mut ends = super::Ends::<1>::new( [0], None );
+            let mut all = Vec::new();
+            while let Some(x) = ends.next( next ) {
+                all.push(x[0]);
+                match ends.next_back( || [n - 1], prev ) {
+                    Some(y) => all.push(y[0]),
+                    None => break,
+                }
+            }
+            assert_eq!( ends.next( next ), None );
+            assert_eq!( ends.next_back( || [n - 1], prev ), None );
+            all.sort();
+            assert_eq!( all, (0..n).collect::<Vec<_>>() );
+
+            let mut ends = super::Ends::<1>::new( [0], None );
+            assert_eq!( ends.next_back( || [n - 1], prev ), Some([n - 1]) );
+            assert_eq!( ends.next( next ), if n == 1 { None } else { Some([0]) } );
+        }
+    }
+
+    #[test]
+    fn hash_key() {
+        use std::collections::HashMap;
+
+        let mut map = HashMap::new();
+        for [a, b] in array::Multichoose::<2>::new( 4 ) {
+            *map.entry([a % 2, b % 2]).or_insert(0) += 1;
+        }
+        assert_eq!( map[&[0, 1]], 3 );
+        assert_eq!( map.values().sum::<i32>(), 10 );
+    }
+}
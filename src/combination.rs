@@ -1,135 +1,543 @@
+use crate::{count, Error, Items, Order, Streaming};
+use crate::cursor::Cursor;
+use core::convert::TryFrom;
+use core::ops::ControlFlow;
+use crate::successor::combination::{next_state, prev_state};
+use alloc::vec;
+use alloc::vec::Vec;
+
 
 #[derive(Debug, Clone)]
 pub struct Combination {
-    state: Vec<usize>,
-    status: Status,
+    cursor: Cursor,
     dim: usize,
     n: usize,
-    end: Vec<usize>,
-}
-
-#[derive(Debug, Clone)]
-enum Status {
-    Ini,
-    Run,
-    End,
+    order: Order,
 }
 
 impl Combination {
+    /// Generates all increasing sequences of `dim` numbers out of `0..n`.
+    ///
+    /// There is exactly one (empty) combination if `dim == 0`, and none if `dim > n`.
     pub fn new( dim: usize, n: usize ) -> Self {
+        Combination::with_order( dim, n, Order::Lex )
+    }
+
+    /// Same as `Combination::new`, but generates the combinations in `order`.
+    pub fn with_order( dim: usize, n: usize, order: Order ) -> Self {
+        let first = if order.is_rev() { last( dim, n ) } else { (0..dim).collect() };
         Combination {
-            state: (0..dim).collect(),
-            status: Status::Ini,
+            cursor: Cursor::new( first, dim, count::binomial_u128( n, dim ) ),
             dim,
             n,
-            end: (n-dim..n).collect(),
+            order,
         }
     }
+
+    /// Same as `Combination::new`, but returns an error instead of an iterator generating
+    /// no combinations if `dim > n`.
+    pub fn try_new( dim: usize, n: usize ) -> Result<Self, Error> {
+        if dim > n {
+            Err( Error::DimTooLarge { dim, n } )
+        } else {
+            Ok( Combination::new( dim, n ) )
+        }
+    }
+
+    /// Generates all choices of `dim` distinct elements of `items`, in the order of `items`, as references.
+    ///
+    /// Same as `Combination::new( dim, items.len() )` with each index `i` replaced by `&items[i]`.
+    pub fn of<T>( items: &[T], dim: usize ) -> Items<'_, T, Self> {
+        Items::new( items, Combination::new( dim, items.len() ) )
+    }
+
+    /// Order of the combinations.
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
+    /// Number of all combinations, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::binomial_u128( self.n, self.dim )
+    }
+
+    /// Number of combinations not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.cursor.remaining()
+    }
+
+    /// Returns the `idx`-th combination (counting from 0) in the order of the iterator,
+    /// or `None` if there are not so many combinations.
+    pub fn from_index( dim: usize, n: usize, idx: usize ) -> Option<Vec<usize>> {
+        unrank( dim, n, idx as u128 )
+    }
+
+    /// Returns the position of `comb` in the order of the iterator, that is, the inverse of
+    /// `Combination::from_index`.
+    ///
+    /// Returns `None` if `comb` is not a strictly increasing sequence of numbers less than `n`
+    /// or the index does not fit in `usize`.
+    pub fn to_index( comb: &[usize], n: usize ) -> Option<usize> {
+        usize::try_from( rank( comb, n, Order::Lex )? ).ok()
+    }
+
+    /// Same as `Combination::from_index`, for `Combination::with_order( dim, n, order )`.
+    pub fn from_index_with_order( dim: usize, n: usize, order: Order, idx: u128 ) -> Option<Vec<usize>> {
+        unrank_with_order( dim, n, order, idx )
+    }
+
+    /// Same as `Combination::to_index`, for `Combination::with_order( comb.len(), n, order )`.
+    ///
+    /// For the reverse orders, this also returns `None` if the number of combinations
+    /// does not fit in `u128`.
+    pub fn to_index_with_order( comb: &[usize], n: usize, order: Order ) -> Option<u128> {
+        rank( comb, n, order )
+    }
+
+    /// Moves the iterator so that the next call of `next()` returns the `idx`-th combination,
+    /// without generating the ones in between.
+    pub fn seek( &mut self, idx: u128 ) {
+        let (dim, n, order) = (self.dim, self.n, self.order);
+        self.cursor.seek( idx, || unrank_with_order( dim, n, order, idx ) );
+    }
+
+    /// Position of the combination which the next call of `next()` returns.
+    pub fn next_index(&self) -> u128 {
+        self.cursor.next_index()
+    }
+
+    /// Calls `f` on each remaining combination by reference without allocating, until `f` returns
+    /// `ControlFlow::Break`. The iterator can be resumed after it.
+    pub fn for_each_slice<B, F>( &mut self, f: F ) -> ControlFlow<B>
+        where F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        let (n, order) = (self.n, self.order);
+        self.cursor.for_each(|state| advance( state, n, order, true ), f)
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
+
+    fn step(&mut self) {
+        let (n, order) = (self.n, self.order);
+        self.cursor.step(|state| advance( state, n, order, true ));
+    }
+
+    fn step_back(&mut self) {
+        let (dim, n, order) = (self.dim, self.n, self.order);
+        let last = || if order.is_rev() { (0..dim).collect() } else { last( dim, n ) };
+        self.cursor.step_back(last, |state| advance( state, n, order, false ));
+    }
 }
 
 
-#[cfg(feature = "streaming")]
-mod streaming_iterator {
-    use super::{Combination, Status};
-    use streaming_iterator::StreamingIterator;
+/// The last combination in lexicographic order, which is also the last one in colexicographic order.
+fn last( dim: usize, n: usize ) -> Vec<usize> {
+    // dim > n なら空で、中身は使われない
+    (n.saturating_sub(dim)..n).collect()
+}
+
+
+/// Position of `comb` in `order`, or `None` if `comb` is not a strictly increasing sequence
+/// of numbers less than `n` or the index does not fit in `u128`.
+pub(crate) fn rank( comb: &[usize], n: usize, order: Order ) -> Option<u128> {
+    let dim = comb.len();
+    let mut idx: u128 = 0;
+    let mut x = 0;
+
+    for (k, &c) in comb.iter().enumerate() {
+        if c < x || c >= n {
+            return None;
+        }
+        let before = if order.is_colex() {
+            // c より小さい数からなる k+1 個の組
+            count::binomial_u128( c, k + 1 )?
+        } else {
+            smaller( n, x, dim-1-k, c )?
+        };
+        idx = idx.checked_add( before )?;
+        x = c + 1;
+    }
+
+    if order.is_rev() {
+        Some( count::binomial_u128( n, dim )? - 1 - idx )
+    } else {
+        Some(idx)
+    }
+}
+
+/// Same as `Combination::from_index_with_order`.
+pub(crate) fn unrank_with_order( dim: usize, n: usize, order: Order, idx: u128 ) -> Option<Vec<usize>> {
+    let idx = if order.is_rev() {
+        count::binomial_u128( n, dim )?.checked_sub(1)?.checked_sub(idx)?
+    } else {
+        idx
+    };
+
+    if order.is_colex() { unrank_colex( dim, n, idx ) } else { unrank( dim, n, idx ) }
+}
+
+
+/// Same as `Combination::from_index`, with the index as `u128`.
+///
+/// Each element is found by a binary search, so this takes `O(dim log n)` binomial coefficients.
+fn unrank( dim: usize, n: usize, idx: u128 ) -> Option<Vec<usize>> {
+    if dim > n || count::binomial_u128( n, dim ).map_or(false, |total| idx >= total) {
+        return None;
+    }
+
+    let mut idx = idx;
+    let mut vec = Vec::with_capacity(dim);
+    let mut lo = 0;
+
+    for k in 0..dim {
+        let m = dim - 1 - k;
+
+        // smaller(x) <= idx となる最大の x を二分探索する
+        let (mut a, mut b) = (lo, n - m);
+        while b - a > 1 {
+            let mid = a + (b - a) / 2;
+            if smaller( n, lo, m, mid ).map_or(false, |s| s <= idx) { a = mid; } else { b = mid; }
+        }
+
+        idx -= smaller( n, lo, m, a )?;
+        vec.push(a);
+        lo = a + 1;
+    }
+
+    Some(vec)
+}
+
+/// Same as `unrank`, in colexicographic order.
+fn unrank_colex( dim: usize, n: usize, idx: u128 ) -> Option<Vec<usize>> {
+    if dim > n || count::binomial_u128( n, dim ).map_or(false, |total| idx >= total) {
+        return None;
+    }
+
+    let mut idx = idx;
+    let mut vec = vec![ 0; dim ];
+    let mut hi = n;
+
+    // 大きい方から、binomial(x, k+1) <= idx となる最大の x を二分探索する
+    for k in (0..dim).rev() {
+        let (mut a, mut b) = (k, hi);
+        while b - a > 1 {
+            let mid = a + (b - a) / 2;
+            if count::binomial_u128( mid, k + 1 ).map_or(false, |c| c <= idx) { a = mid; } else { b = mid; }
+        }
+        idx -= count::binomial_u128( a, k + 1 )?;
+        vec[k] = a;
+        hi = a;
+    }
 
-    impl StreamingIterator for Combination {
+    Some(vec)
+}
+
+/// Number of ways to choose `m+1` increasing numbers from `lo..n` with the first one less than `x`,
+/// or `None` if it does not fit in `u128`.
+fn smaller( n: usize, lo: usize, m: usize, x: usize ) -> Option<u128> {
+    // binomial(n-1-y, m) の y = lo..x についての和
+    match count::binomial_u128( n - lo, m + 1 ) {
+        Some(all) => Some( all - count::binomial_u128( n - x, m + 1 )? ),
+        None => (lo..x).try_fold(0u128, |sum, y| sum.checked_add( count::binomial_u128( n-1-y, m )? )),
+    }
+}
+
+/// Rearranges `state` into the next combination in `order` if `forward`, or the previous one
+/// otherwise. Returns `false` if there is no such combination.
+fn advance( state: &mut [usize], n: usize, order: Order, forward: bool ) -> bool {
+    match (order.is_colex(), forward != order.is_rev()) {
+        (false, true) => next_state( state, n ),
+        (false, false) => prev_state( state, n ),
+        (true, true) => next_colex( state, n ),
+        (true, false) => prev_colex( state ),
+    }
+}
+
+
+/// Rearranges `state` into the next combination in colexicographic order,
+/// or returns `false` if it is the last one.
+fn next_colex( state: &mut [usize], n: usize ) -> bool {
+    let dim = state.len();
+
+    // 1 を足せる一番左の位置を探し、それより左は詰めて並べる
+    match (0..dim).find(|&i| state[i] + 1 < if i + 1 == dim { n } else { state[i+1] }) {
+        Some(i) => {
+            state[i] += 1;
+            for (j, x) in state[..i].iter_mut().enumerate() {
+                *x = j;
+            }
+            true
+        },
+        None => false,
+    }
+}
+
+/// Rearranges `state` into the previous combination in colexicographic order,
+/// or returns `false` if it is the first one.
+fn prev_colex( state: &mut [usize] ) -> bool {
+    // 1 を引ける一番左の位置を探し、それより左は最大にする
+    match (0..state.len()).find(|&i| state[i] > i) {
+        Some(i) => {
+            state[i] -= 1;
+            let x = state[i];
+            for (j, y) in state[..i].iter_mut().enumerate() {
+                *y = x - i + j;
+            }
+            true
+        },
+        None => false,
+    }
+}
+
+
+/// Generates all combinations of `dim` numbers out of `0..n` in the revolving-door order,
+/// where each combination differs from the previous one by removing one number and
+/// adding another.
+///
+/// Each item is sorted in increasing order, and `change()` reports which numbers were
+/// exchanged.
+#[derive(Debug, Clone)]
+pub struct RevolvingDoor {
+    /// The combination followed by `n` as a sentinel.
+    state: Vec<usize>,
+    dim: usize,
+    n: usize,
+    change: Option<(usize, usize)>,
+    ok: bool,
+    taken: u128,
+}
+
+impl RevolvingDoor {
+    /// Generates all combinations of `dim` numbers out of `0..n`, starting from `[0, 1, ..., dim-1]`.
+    pub fn new( dim: usize, n: usize ) -> RevolvingDoor {
+        let mut state: Vec<usize> = (0..dim).collect();
+        state.push(n);
+        RevolvingDoor { state, dim, n, change: None, ok: false, taken: 0 }
+    }
+
+    /// Number of all combinations, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::binomial_u128( self.n, self.dim )
+    }
+
+    /// Number of combinations not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.total().map(|total| total - self.taken)
+    }
+
+    /// The pair `(removed, added)` of numbers exchanged to reach the current combination
+    /// from the previous one, or `None` for the first one.
+    pub fn change(&self) -> Option<(usize, usize)> {
+        self.change
+    }
+
+    /// Calls `f` on each remaining combination by reference without allocating, until `f` returns
+    /// `ControlFlow::Break`. The iterator can be resumed after it.
+    pub fn for_each_slice<B, F>( &mut self, mut f: F ) -> ControlFlow<B>
+        where F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        loop {
+            self.step();
+            if !self.ok {
+                return ControlFlow::Continue(());
+            }
+            if let ControlFlow::Break(b) = f( &self.state[..self.dim] ) {
+                return ControlFlow::Break(b);
+            }
+        }
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
+
+    fn step(&mut self) {
+        if self.taken == 0 {
+            self.ok = self.dim <= self.n;
+            self.taken = if self.ok { 1 } else { 0 };
+            return;
+        }
+        if !self.ok {
+            return;
+        }
+
+        self.ok = false;
+        if self.dim == 0 {
+            return;
+        }
+        self.change = revolve( &mut self.state, self.dim );
+        if self.change.is_some() {
+            self.ok = true;
+            self.taken += 1;
+        }
+    }
+}
+
+/// Rearranges `state` into the next combination in the revolving-door order by Knuth's Algorithm R,
+/// and returns the pair `(removed, added)`, or `None` if it is the last one.
+///
+/// `state[dim]` must be `n`, and `dim` must be positive.
+fn revolve( state: &mut [usize], dim: usize ) -> Option<(usize, usize)> {
+    // 教科書の c_j は state[j-1]
+    let odd = dim % 2 == 1;
+    if odd && state[0] + 1 < state[1] {
+        state[0] += 1;
+        return Some((state[0] - 1, state[0]));
+    }
+    if !odd && state[0] > 0 {
+        state[0] -= 1;
+        return Some((state[0] + 1, state[0]));
+    }
+
+    // state[j] を減らす (R4) か増やす (R5) かを交互に試す
+    let mut decrease = odd;
+    for j in 1..dim {
+        if decrease && state[j] > j {
+            let removed = state[j];
+            state[j] = state[j-1];
+            state[j-1] = j - 1;
+            return Some((removed, j - 1));
+        }
+        if !decrease && state[j] + 1 < state[j+1] {
+            let removed = state[j-1];
+            state[j-1] = state[j];
+            state[j] += 1;
+            return Some((removed, state[j]));
+        }
+        decrease = !decrease;
+    }
+    None
+}
+
+
+mod streaming {
+    use super::{Combination, RevolvingDoor};
+    use crate::count;
+    use crate::streaming::{Source, SourceBack};
+
+    impl Source for Combination {
         type Item = [usize];
 
         fn advance(&mut self) {
-            match self.status {
-                Status::Ini => { self.status = Status::Run; },
-                Status::Run => {
-                    if self.state == self.end {
-                        self.status = Status::End;
-                    } else {
-                        // 1 を足す
-                        self.state[self.dim-1] += 1;
-                        
-                        while {
-                            // 繰り上げ処理
-                            for idx in (1..self.dim).rev() {
-                                if self.state[idx] == self.n {
-                                    self.state[idx-1] += 1;
-                                    self.state[idx] = 0;
-                                }
-                            }
-                            for idx in 1..self.dim {
-                                if self.state[idx] == 0 {
-                                    self.state[idx] = self.state[idx-1] + 1;
-                                }
-                            }
-
-                            self.state[self.dim-1] >= self.n
-                        } {}
-                    }
-                },
-                Status::End => {},
-            }
+            self.step();
         }
 
         fn get(&self) -> Option<&[usize]> {
-            match self.status {
-                Status::Run => Some(&self.state),
-                _ => None,
-            }
+            self.cursor.current()
+        }
+
+        fn jump(&mut self, k: usize) {
+            self.seek( self.next_index().saturating_add(k as u128) );
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
         }
     }
-}
 
+    impl SourceBack for Combination {
+        fn advance_back(&mut self) {
+            self.step_back();
+        }
+    }
+
+    impl Source for RevolvingDoor {
+        type Item = [usize];
+
+        fn advance(&mut self) {
+            self.step();
+        }
+
+        fn get(&self) -> Option<&[usize]> {
+            if self.ok { Some(&self.state[..self.dim]) } else { None }
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.remaining() )
+        }
+    }
+}
 
 
-#[cfg(not(feature = "streaming"))]
 mod iterator {
-    use super::{Combination, Status};
-    use std::iter::Iterator;
+    use super::{Combination, RevolvingDoor};
+    use crate::count;
+    use alloc::vec::Vec;
+    use core::iter::{Iterator, DoubleEndedIterator};
 
     impl Iterator for Combination {
         type Item = Vec<usize>;
 
         fn next(&mut self) -> Option<Vec<usize>> {
-            match self.status {
-                Status::Ini => { self.status = Status::Run; },
-                Status::Run => {
-                    if self.state == self.end {
-                        self.status = Status::End;
-                    } else {
-                        // 1 を足す
-                        self.state[self.dim-1] += 1;
-                        
-                        while {
-                        
-                        // 繰り上げ処理
-                        for idx in (1..self.dim).rev() {
-                            if self.state[idx] == self.n {
-                                self.state[idx-1] += 1;
-                                self.state[idx] = 0;
-                            }
-                        }
-                        for idx in 1..self.dim {
-                            if self.state[idx] == 0 {
-                                self.state[idx] = self.state[idx-1] + 1;
-                            }
-                        }
-
-                        self.state[self.dim-1] >= self.n
-                    } {}
-                    }
-                },
-                Status::End => {},
-            }
+            self.step();
+            self.cursor.front().map(|comb| comb.to_vec())
+        }
 
-            match self.status {
-                Status::Run => Some(self.state.clone()),
-                _ => None,
-            }
+        fn nth(&mut self, k: usize) -> Option<Vec<usize>> {
+            self.seek( self.next_index().saturating_add(k as u128) );
+            self.next()
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl DoubleEndedIterator for Combination {
+        fn next_back(&mut self) -> Option<Vec<usize>> {
+            self.step_back();
+            self.cursor.back().map(|comb| comb.to_vec())
+        }
+    }
+
+    impl Iterator for RevolvingDoor {
+        type Item = Vec<usize>;
+
+        fn next(&mut self) -> Option<Vec<usize>> {
+            self.step();
+            if self.ok { Some(self.state[..self.dim].to_vec()) } else { None }
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.remaining() )
         }
     }
 }
 
 
+#[cfg(feature = "rand")]
+mod sample {
+    use super::Combination;
+    use alloc::vec::Vec;
+    use rand::Rng;
 
+    impl Combination {
+        /// Returns a combination of `dim` numbers out of `0..n` chosen uniformly at random,
+        /// or `None` if `dim > n`. It is increasing, as the iterator generates.
+        ///
+        /// This takes `O(dim^2)` time and `O(dim)` memory (Floyd's algorithm), regardless of `n`.
+        pub fn sample<R: Rng + ?Sized>( dim: usize, n: usize, rng: &mut R ) -> Option<Vec<usize>> {
+            if dim > n {
+                return None;
+            }
 
+            let mut set: Vec<usize> = Vec::with_capacity(dim);
+            for j in n-dim..n {
+                let t = rng.gen_range( 0..=j );
+                // set の要素はすべて j より小さいので、t が既出なら j を末尾に足せばよい
+                match set.binary_search(&t) {
+                    Ok(_) => set.push(j),
+                    Err(pos) => set.insert( pos, t ),
+                }
+            }
+            Some(set)
+        }
+    }
+}
 
 
 #[cfg(test)]
@@ -170,4 +578,288 @@ mod tests {
         assert_eq!( iter.next().unwrap(), &[2,3,4] );
         assert_eq!( iter.next(), None );
     }
+
+
+    #[test]
+    fn from_index() {
+        let dim = 3;
+        let n = 6;
+
+        let mut iter = Combination::new( dim, n );
+        let mut idx = 0;
+
+        for elem in iter.by_ref() {
+            let vec = Combination::from_index( dim, n, idx ).unwrap();
+            let vec: &[usize] = &vec;
+            assert_eq!( elem, vec );
+            assert_eq!( Combination::to_index( vec, n ), Some(idx) );
+            idx += 1;
+        }
+
+        assert_eq!( Combination::from_index( dim, n, idx ), None );
+        assert_eq!( Combination::to_index( &[0,2,1], n ), None );
+        assert_eq!( Combination::to_index( &[0,1,6], n ), None );
+    }
+
+
+    #[test]
+    fn size_hint() {
+        let mut iter = Combination::new( 3, 5 );
+        assert_eq!( iter.total(), Some(10) );
+
+        for rest in (0..10).rev() {
+            assert_eq!( iter.size_hint(), (rest+1, Some(rest+1)) );
+            iter.next().unwrap();
+            assert_eq!( iter.remaining(), Some(rest as u128) );
+        }
+
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.size_hint(), (0, Some(0)) );
+    }
+
+
+    #[test]
+    fn next_back() {
+        let dim = 3;
+        let n = 6;
+
+        let mut forward = Vec::new();
+        let mut iter = Combination::new( dim, n );
+        for elem in iter.by_ref() {
+            forward.push( elem.to_vec() );
+        }
+
+        let mut backward = Vec::new();
+        let mut iter = Combination::new( dim, n );
+        while let Some(elem) = iter.next_back() {
+            backward.push( elem.to_vec() );
+        }
+        backward.reverse();
+        assert_eq!( backward, forward );
+
+        // 両端から交互に取り出す
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        let mut iter = Combination::new( dim, n );
+        while let Some(elem) = iter.next() {
+            front.push( elem.to_vec() );
+            match iter.next_back() {
+                Some(elem) => back.push( elem.to_vec() ),
+                None => break,
+            }
+        }
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.next_back(), None );
+        back.reverse();
+        front.append( &mut back );
+        assert_eq!( front, forward );
+    }
+
+
+    #[test]
+    fn seek() {
+        let dim = 3;
+        let n = 6;
+
+        let mut all = Vec::new();
+        let mut iter = Combination::new( dim, n );
+        for elem in iter.by_ref() {
+            all.push( elem.to_vec() );
+        }
+
+        for (idx, elem) in all.iter().enumerate() {
+            let mut iter = Combination::new( dim, n );
+            iter.seek( idx as u128 );
+            assert_eq!( iter.next().unwrap(), &elem[..] );
+            assert_eq!( iter.next_index(), idx as u128 + 1 );
+        }
+
+        let mut iter = Combination::new( dim, n );
+        assert_eq!( iter.nth(3).unwrap(), &all[3][..] );
+        assert_eq!( iter.nth(2).unwrap(), &all[6][..] );
+        assert_eq!( iter.nth(all.len()), None );
+        iter.seek( 1 );
+        assert_eq!( iter.next().unwrap(), &all[1][..] );
+
+        // 後ろから取り出したものには飛ばない
+        let mut iter = Combination::new( dim, n );
+        iter.next_back();
+        iter.seek( all.len() as u128 - 1 );
+        assert_eq!( iter.next(), None );
+
+        // 添字が usize に収まらない場合
+        let mut iter = Combination::new( 60, 120 );
+        let idx = iter.total().unwrap() / 3;
+        iter.seek( idx );
+        let elem = iter.next().unwrap().to_vec();
+        let next = iter.next().unwrap().to_vec();
+        iter.seek( idx + 1 );
+        assert_eq!( iter.next().unwrap(), &next[..] );
+        assert_ne!( elem, next );
+    }
+
+
+    #[test]
+    fn edge_cases() {
+        // 空の列がちょうど 1 つ
+        for n in 0..3 {
+            let mut iter = Combination::new( 0, n );
+            assert_eq!( iter.total(), Some(1) );
+            assert_eq!( iter.next().unwrap(), &[] );
+            assert_eq!( iter.next(), None );
+            assert_eq!( iter.next_back(), None );
+
+            let mut iter = Combination::new( 0, n );
+            assert_eq!( iter.next_back().unwrap(), &[] );
+            assert_eq!( iter.next(), None );
+            assert!( Combination::try_new( 0, n ).is_ok() );
+        }
+
+        let mut iter = Combination::new( 4, 3 );
+        assert_eq!( iter.total(), Some(0) );
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.next_back(), None );
+        assert_eq!( Combination::try_new( 4, 3 ).unwrap_err(), Error::DimTooLarge { dim: 4, n: 3 } );
+    }
+
+
+    #[test]
+    fn revolving_door() {
+        let mut iter = combination::RevolvingDoor::new( 2, 4 );
+        let mut all = Vec::new();
+        while let Some(comb) = iter.next() {
+            all.push(( comb.to_vec(), iter.change() ));
+        }
+        assert_eq!( all, vec![
+            (vec![0,1], None),
+            (vec![1,2], Some((0,2))),
+            (vec![0,2], Some((1,0))),
+            (vec![2,3], Some((0,3))),
+            (vec![1,3], Some((2,1))),
+            (vec![0,3], Some((1,0))),
+        ]);
+
+        let iter = combination::RevolvingDoor::new( 40, 80 );
+        assert_eq!( iter.remaining(), count::binomial_u128( 80, 40 ) );
+        assert_eq!( iter.size_hint(), (usize::MAX, None) );
+
+        for n in 0..8 {
+            for dim in 0..n+2 {
+                let mut iter = combination::RevolvingDoor::new( dim, n );
+                let mut all: Vec<Vec<usize>> = Vec::new();
+                while let Some(comb) = iter.next() {
+                    let comb = comb.to_vec();
+                    if let Some((removed, added)) = iter.change() {
+                        let mut prev = all.last().unwrap().clone();
+                        prev.retain(|&x| x != removed);
+                        prev.push(added);
+                        prev.sort();
+                        assert_eq!( prev, comb );
+                    }
+                    all.push(comb);
+                }
+                assert_eq!( iter.remaining(), Some(0) );
+                all.sort();
+                let mut expected = Vec::new();
+                let mut iter = Combination::new( dim, n );
+                for comb in iter.by_ref() {
+                    expected.push(comb.to_vec());
+                }
+                assert_eq!( all, expected );
+            }
+        }
+    }
+
+
+    #[test]
+    fn order() {
+        let (dim, n) = (3, 6);
+        let mut lex = Vec::new();
+        let mut iter = Combination::new( dim, n );
+        for x in iter.by_ref() {
+            lex.push(x.to_vec());
+        }
+
+        for &order in &[Order::Lex, Order::Colex, Order::RevLex, Order::RevColex] {
+            let mut expected = lex.clone();
+            if order == Order::Colex || order == Order::RevColex {
+                expected.sort_by_key(|x| x.iter().rev().cloned().collect::<Vec<usize>>());
+            }
+            if order == Order::RevLex || order == Order::RevColex {
+                expected.reverse();
+            }
+
+            let mut iter = Combination::with_order( dim, n, order );
+            assert_eq!( iter.order(), order );
+            let mut idx = 0;
+            for x in iter.by_ref() {
+                let x = x.to_vec();
+                assert_eq!( x, expected[idx] );
+                assert_eq!( Combination::from_index_with_order( dim, n, order, idx as u128 ), Some(x.clone()) );
+                assert_eq!( Combination::to_index_with_order( &x, n, order ), Some(idx as u128) );
+                idx += 1;
+            }
+            assert_eq!( idx, expected.len() );
+            assert_eq!( Combination::from_index_with_order( dim, n, order, idx as u128 ), None );
+
+            let mut iter = Combination::with_order( dim, n, order );
+            let mut idx = expected.len();
+            while let Some(x) = iter.next_back() {
+                idx -= 1;
+                assert_eq!( x.to_vec(), expected[idx] );
+            }
+            assert_eq!( idx, 0 );
+
+            let mut iter = Combination::with_order( dim, n, order );
+            iter.seek( 7 );
+            assert_eq!( iter.next().unwrap().to_vec(), expected[7] );
+        }
+
+        // 0..m からなるものが先に来る
+        let mut iter = Combination::with_order( 2, n, Order::Colex );
+        assert_eq!( iter.next().unwrap(), &[0,1] );
+        assert_eq!( iter.nth( 1 ).unwrap(), &[1,2] );
+    }
+
+
+    #[test]
+    fn for_each_slice() {
+        use std::ops::ControlFlow;
+
+        for &order in &[Order::Lex, Order::Colex, Order::RevLex] {
+            let mut all = Vec::new();
+            let flow: ControlFlow<()> = Combination::with_order( 3, 6, order ).for_each_slice(|comb| {
+                all.push(comb.to_vec());
+                ControlFlow::Continue(())
+            });
+            assert_eq!( flow, ControlFlow::Continue(()) );
+            assert_eq!( all, Combination::with_order( 3, 6, order ).collect::<Vec<_>>() );
+        }
+
+        let mut iter = combination::RevolvingDoor::new( 3, 6 );
+        let flow = iter.for_each_slice(|comb| {
+            if comb.contains(&5) { ControlFlow::Break(comb.to_vec()) } else { ControlFlow::Continue(()) }
+        });
+        assert_eq!( flow, ControlFlow::Break(vec![0,4,5]) );
+        assert_eq!( iter.remaining(), Some(9) );
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn sample() {
+        use rand::{SeedableRng, rngs::SmallRng};
+
+        let mut rng = SmallRng::seed_from_u64( 1 );
+        let mut hist = [0; 20];
+        for _ in 0..20000 {
+            let comb = Combination::sample( 3, 6, &mut rng ).unwrap();
+            hist[ Combination::to_index( &comb, 6 ).unwrap() ] += 1;
+        }
+        assert!( hist.iter().all(|&c| 800 < c && c < 1200), "{:?}", hist );
+
+        let comb = Combination::sample( 5, usize::MAX, &mut rng ).unwrap();
+        assert!( comb.windows(2).all(|w| w[0] < w[1]) );
+        assert_eq!( Combination::sample( 4, 4, &mut rng ), Some(vec![0, 1, 2, 3]) );
+        assert_eq!( Combination::sample( 3, 2, &mut rng ), None );
+    }
 }
@@ -0,0 +1,695 @@
+//! Compositions of an integer `n`, that is, sequences of `k` parts summing to `n`.
+//!
+//! The parts of `Composition::new` are positive, those of `Composition::weak` may be 0,
+//! and `Composition::with_bounds` takes the range of each part. They are generated
+//! in lexicographic order.
+//!
+//! ```
+//! use enumcombinatorics::*;
+//!
+//! let mut iter = Composition::weak( 2, 3 );
+//!
+//! assert_eq!( iter.next().unwrap(), &[0,0,2] );
+//! assert_eq!( iter.next().unwrap(), &[0,1,1] );
+//! assert_eq!( iter.next().unwrap(), &[0,2,0] );
+//! assert_eq!( iter.next().unwrap(), &[1,0,1] );
+//! assert_eq!( iter.next().unwrap(), &[1,1,0] );
+//! assert_eq!( iter.next().unwrap(), &[2,0,0] );
+//! assert_eq!( iter.next(), None );
+//!
+//! let mut iter = Composition::with_bounds( 5, &[ 0..=1, 2..=3, 1..=5 ] );
+//!
+//! assert_eq!( iter.next().unwrap(), &[0,2,3] );
+//! assert_eq!( iter.next().unwrap(), &[0,3,2] );
+//! assert_eq!( iter.next().unwrap(), &[1,2,2] );
+//! assert_eq!( iter.next().unwrap(), &[1,3,1] );
+//! assert_eq!( iter.next(), None );
+//! ```
+//!
+//! A weak composition corresponds to the multiset of its partial sums ("stars and bars"),
+//! which `Multichoose` generates in the same order (see `to_multiset`).
+
+
+use crate::{count, Multichoose, Order, Streaming};
+use crate::cursor::Cursor;
+use core::convert::TryFrom;
+use core::ops::{ControlFlow, RangeInclusive};
+use alloc::vec;
+use alloc::vec::Vec;
+
+
+#[derive(Debug, Clone)]
+pub struct Composition {
+    cursor: Cursor,
+    bounds: Bounds,
+}
+
+impl Composition {
+    /// Generates all compositions of `n` into `k` positive parts in lexicographic order.
+    ///
+    /// There is exactly one (empty) composition if `n == 0` and `k == 0`.
+    pub fn new( n: usize, k: usize ) -> Self {
+        Composition::with_bounds( n, &vec![ 1..=n; k ] )
+    }
+
+    /// Same as `Composition::new`, but the parts may be 0.
+    pub fn weak( n: usize, k: usize ) -> Self {
+        Composition::with_bounds( n, &vec![ 0..=n; k ] )
+    }
+
+    /// Generates all compositions of `n` whose `i`-th part is in `bounds[i]`.
+    ///
+    /// Unless the upper bounds are too large to matter, this takes `O(n k)` time and memory
+    /// to count them, where `k` is `bounds.len()`.
+    pub fn with_bounds( n: usize, bounds: &[RangeInclusive<usize>] ) -> Self {
+        let bounds = Bounds::new( n, bounds );
+        Composition {
+            cursor: Cursor::new( first( &bounds ), bounds.lower.len(), total( &bounds ) ),
+            bounds,
+        }
+    }
+
+    /// Number of all compositions, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        total( &self.bounds )
+    }
+
+    /// Number of compositions not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.cursor.remaining()
+    }
+
+    /// Returns the `idx`-th composition (counting from 0) of `Composition::new( n, k )`,
+    /// or `None` if there are not so many compositions.
+    pub fn from_index( n: usize, k: usize, idx: usize ) -> Option<Vec<usize>> {
+        unrank( &Bounds::new( n, &vec![ 1..=n; k ] ), idx as u128 )
+    }
+
+    /// Returns the position of `parts` in `Composition::new( n, k )`, where `n` is the sum of
+    /// `parts` and `k` is its length, that is, the inverse of `Composition::from_index`.
+    ///
+    /// Returns `None` if `parts` contains 0 or the index does not fit in `usize`.
+    pub fn to_index( parts: &[usize] ) -> Option<usize> {
+        let n = sum( parts )?;
+        usize::try_from( rank( parts, &Bounds::new( n, &vec![ 1..=n; parts.len() ] ) )? ).ok()
+    }
+
+    /// Same as `Composition::from_index`, for `Composition::weak( n, k )`.
+    pub fn from_index_weak( n: usize, k: usize, idx: usize ) -> Option<Vec<usize>> {
+        unrank( &Bounds::new( n, &vec![ 0..=n; k ] ), idx as u128 )
+    }
+
+    /// Same as `Composition::to_index`, for `Composition::weak( n, k )`.
+    ///
+    /// Returns `None` if the index does not fit in `usize`.
+    pub fn to_index_weak( parts: &[usize] ) -> Option<usize> {
+        let n = sum( parts )?;
+        usize::try_from( rank( parts, &Bounds::new( n, &vec![ 0..=n; parts.len() ] ) )? ).ok()
+    }
+
+    /// Same as `Composition::from_index`, for `Composition::with_bounds( n, bounds )`.
+    ///
+    /// This also returns `None` if the number of compositions does not fit in `u128`.
+    pub fn from_index_with_bounds( n: usize, bounds: &[RangeInclusive<usize>], idx: u128 ) -> Option<Vec<usize>> {
+        unrank( &Bounds::new( n, bounds ), idx )
+    }
+
+    /// Same as `Composition::to_index`, for `Composition::with_bounds( n, bounds )`
+    /// where `n` is the sum of `parts`.
+    ///
+    /// Returns `None` if `parts` does not fit in `bounds`
+    /// or the number of compositions does not fit in `u128`.
+    pub fn to_index_with_bounds( parts: &[usize], bounds: &[RangeInclusive<usize>] ) -> Option<u128> {
+        rank( parts, &Bounds::new( sum( parts )?, bounds ) )
+    }
+
+    /// Moves the iterator so that the next call of `next()` returns the `idx`-th composition,
+    /// without generating the ones in between.
+    pub fn seek( &mut self, idx: u128 ) {
+        let bounds = &self.bounds;
+        self.cursor.seek( idx, || unrank( bounds, idx ) );
+    }
+
+    /// Position of the composition which the next call of `next()` returns.
+    pub fn next_index(&self) -> u128 {
+        self.cursor.next_index()
+    }
+
+    /// Calls `f` on each remaining composition by reference without allocating, until `f` returns
+    /// `ControlFlow::Break`. The iterator can be resumed after it.
+    pub fn for_each_slice<B, F>( &mut self, f: F ) -> ControlFlow<B>
+        where F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        let bounds = &self.bounds;
+        self.cursor.for_each(|state| next_state( state, bounds ), f)
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
+
+    fn step(&mut self) {
+        let bounds = &self.bounds;
+        self.cursor.step(|state| next_state( state, bounds ));
+    }
+
+    fn step_back(&mut self) {
+        let bounds = &self.bounds;
+        self.cursor.step_back(|| last( bounds ), |state| prev_state( state, bounds ));
+    }
+}
+
+
+/// Converts a weak composition into the partial sums of its parts but the last,
+/// which is a multiset of `k-1` numbers out of `0..n+1`.
+///
+/// This keeps the order, so the weak compositions of `n` into `k` parts correspond to
+/// `Multichoose::new( k-1, n+1 )` one by one. The parts are all positive if and only if
+/// the partial sums are distinct and positive.
+pub fn to_multiset( parts: &[usize] ) -> Vec<usize> {
+    let len = parts.len().saturating_sub(1);
+    parts[..len].iter().scan(0, |sum, &x| { *sum += x; Some(*sum) }).collect()
+}
+
+/// Converts a multiset of numbers out of `0..n+1` into a weak composition of `n`,
+/// that is, the inverse of `to_multiset`.
+///
+/// Returns `None` if `multiset` is not non-decreasing or contains a number greater than `n`.
+pub fn from_multiset( multiset: &[usize], n: usize ) -> Option<Vec<usize>> {
+    let mut parts = Vec::with_capacity(multiset.len() + 1);
+    let mut prev = 0;
+
+    for &x in multiset.iter().chain(core::iter::once(&n)) {
+        parts.push( x.checked_sub(prev)? );
+        prev = x;
+    }
+
+    Some(parts)
+}
+
+
+/// Sum of `parts`, or `None` if it does not fit in `usize`.
+fn sum( parts: &[usize] ) -> Option<usize> {
+    parts.iter().try_fold(0usize, |sum, &x| sum.checked_add(x))
+}
+
+
+/// The bounds of the parts and the sums needed to generate the compositions.
+#[derive(Debug, Clone)]
+struct Bounds {
+    n: usize,
+    lower: Vec<usize>,
+    /// Upper bounds, at most `n`.
+    upper: Vec<usize>,
+    /// `suffix_lower[i]` is the sum of `lower[i..]`.
+    suffix_lower: Vec<usize>,
+    /// `suffix_upper[i]` is the sum of `upper[i..]`.
+    suffix_upper: Vec<usize>,
+}
+
+impl Bounds {
+    fn new( n: usize, bounds: &[RangeInclusive<usize>] ) -> Bounds {
+        let lower: Vec<usize> = bounds.iter().map(|b| *b.start()).collect();
+        let upper: Vec<usize> = bounds.iter().map(|b| n.min(*b.end())).collect();
+        let suffix = |v: &[usize]| {
+            let mut sums = vec![ 0usize; v.len() + 1 ];
+            for (i, &x) in v.iter().enumerate().rev() {
+                sums[i] = sums[i+1].saturating_add(x);
+            }
+            sums
+        };
+
+        Bounds {
+            n,
+            suffix_lower: suffix( &lower ),
+            suffix_upper: suffix( &upper ),
+            lower,
+            upper,
+        }
+    }
+
+    /// Whether there is any composition.
+    fn is_feasible(&self) -> bool {
+        self.lower.iter().zip(&self.upper).all(|(lo, hi)| lo <= hi)
+            && self.suffix_lower[0] <= self.n && self.n <= self.suffix_upper[0]
+    }
+
+    /// What remains of `n` after taking the lower bounds. The bounds must be feasible.
+    fn rest(&self) -> usize {
+        self.n - self.suffix_lower[0]
+    }
+
+    /// Whether the upper bounds are so large that subtracting the lower bounds turns the
+    /// compositions into all weak compositions of `rest()`. The bounds must be feasible.
+    fn is_loose(&self) -> bool {
+        let rest = self.rest();
+        self.lower.iter().zip(&self.upper).all(|(lo, hi)| hi - lo >= rest)
+    }
+}
+
+
+/// The first composition in lexicographic order.
+fn first( bounds: &Bounds ) -> Vec<usize> {
+    let mut state = vec![ 0; bounds.lower.len() ];
+    // 組成がなければ中身は使われない
+    if bounds.is_feasible() {
+        fill_smallest( &mut state, 0, bounds.n, bounds );
+    }
+    state
+}
+
+/// The last composition in lexicographic order.
+fn last( bounds: &Bounds ) -> Vec<usize> {
+    let mut state = vec![ 0; bounds.lower.len() ];
+    if bounds.is_feasible() {
+        fill_largest( &mut state, 0, bounds.n, bounds );
+    }
+    state
+}
+
+/// Writes the lexicographically smallest parts from `state[from]` summing to `r`. They must exist.
+fn fill_smallest( state: &mut [usize], from: usize, r: usize, bounds: &Bounds ) {
+    let mut r = r;
+    for (i, x) in state.iter_mut().enumerate().skip(from) {
+        // 後ろに入りきらない分だけ大きくする
+        *x = bounds.lower[i].max(r.saturating_sub(bounds.suffix_upper[i+1]));
+        r -= *x;
+    }
+}
+
+/// Same as `fill_smallest`, but writes the lexicographically largest parts.
+fn fill_largest( state: &mut [usize], from: usize, r: usize, bounds: &Bounds ) {
+    let mut r = r;
+    for (i, x) in state.iter_mut().enumerate().skip(from) {
+        *x = bounds.upper[i].min(r - bounds.suffix_lower[i+1]);
+        r -= *x;
+    }
+}
+
+/// Rearranges `state` into the next composition in lexicographic order,
+/// or returns `false` if it is the last one.
+fn next_state( state: &mut [usize], bounds: &Bounds ) -> bool {
+    // 増やせる一番右の部分を探す (その後ろの和が 1 減る)
+    let mut after = 0;
+    for i in (0..state.len()).rev() {
+        if state[i] < bounds.upper[i] && after > bounds.suffix_lower[i+1] {
+            state[i] += 1;
+            fill_smallest( state, i + 1, after - 1, bounds );
+            return true;
+        }
+        after += state[i];
+    }
+
+    false
+}
+
+/// Rearranges `state` into the previous composition in lexicographic order,
+/// or returns `false` if it is the first one.
+fn prev_state( state: &mut [usize], bounds: &Bounds ) -> bool {
+    let mut after = 0;
+    for i in (0..state.len()).rev() {
+        if state[i] > bounds.lower[i] && after < bounds.suffix_upper[i+1] {
+            state[i] -= 1;
+            fill_largest( state, i + 1, after + 1, bounds );
+            return true;
+        }
+        after += state[i];
+    }
+
+    false
+}
+
+
+/// Same as `Composition::total`.
+fn total( bounds: &Bounds ) -> Option<u128> {
+    let k = bounds.lower.len();
+    if !bounds.is_feasible() {
+        Some(0)
+    } else if bounds.is_loose() {
+        count::weak_composition_u128( bounds.rest(), k )
+    } else {
+        Some( table( bounds )?[0][bounds.rest()] )
+    }
+}
+
+/// `table[i][r]` is the number of ways to make the parts from the `i`-th sum to `r` more than
+/// their lower bounds, for each `r` which the parts before it leave.
+///
+/// Returns `None` if some of them does not fit in `u128`, in which case the total does not either.
+fn table( bounds: &Bounds ) -> Option<Vec<Vec<u128>>> {
+    let k = bounds.lower.len();
+    let rest = bounds.rest();
+    let widths: Vec<usize> = bounds.lower.iter().zip(&bounds.upper).map(|(lo, hi)| rest.min(hi - lo)).collect();
+
+    let mut table = vec![ vec![ 0u128; rest + 1 ]; k + 1 ];
+    table[k][0] = 1;
+    // froms[i] は i 番目からの部分に残りうる和の最小値。前の部分が取りうる和の最大値を rest で抑えて求める
+    let mut froms = Vec::with_capacity(k);
+    let mut before = 0;
+    for &w in widths.iter() {
+        froms.push(rest - before);
+        before = before.saturating_add(w).min(rest);
+    }
+
+    for i in (0..k).rev() {
+        let (w, from) = (widths[i], froms[i]);
+
+        // 幅 w+1 の窓で和をとる
+        let mut sum = table[i+1][from.saturating_sub(w)..from].iter().try_fold(0u128, |sum, &x| sum.checked_add(x))?;
+        for r in from..rest+1 {
+            sum = sum.checked_add(table[i+1][r])?;
+            table[i][r] = sum;
+            if r >= w {
+                sum -= table[i+1][r - w];
+            }
+        }
+    }
+
+    Some(table)
+}
+
+/// Same as `Composition::from_index_with_bounds`.
+fn unrank( bounds: &Bounds, idx: u128 ) -> Option<Vec<usize>> {
+    let k = bounds.lower.len();
+    if !bounds.is_feasible() {
+        return None;
+    }
+
+    let rest = bounds.rest();
+    let mut parts = if bounds.is_loose() {
+        if k == 0 {
+            if idx > 0 { return None; }
+            Vec::new()
+        } else {
+            from_multiset( &Multichoose::from_index_with_order( k - 1, rest + 1, Order::Lex, idx )?, rest )?
+        }
+    } else {
+        let table = table( bounds )?;
+        if idx >= table[0][rest] {
+            return None;
+        }
+
+        let (mut idx, mut r) = (idx, rest);
+        let mut parts = Vec::with_capacity(k);
+        for row in table[1..].iter() {
+            let mut x = 0;
+            while idx >= row[r - x] {
+                idx -= row[r - x];
+                x += 1;
+            }
+            parts.push(x);
+            r -= x;
+        }
+        parts
+    };
+
+    for (x, &lo) in parts.iter_mut().zip(&bounds.lower) {
+        *x += lo;
+    }
+
+    Some(parts)
+}
+
+/// Same as `Composition::to_index_with_bounds`, where the sum of `parts` is `bounds.n`.
+fn rank( parts: &[usize], bounds: &Bounds ) -> Option<u128> {
+    if parts.len() != bounds.lower.len() || !bounds.is_feasible() {
+        return None;
+    }
+
+    let mut extra = Vec::with_capacity(parts.len());
+    for ((&x, &lo), &hi) in parts.iter().zip(&bounds.lower).zip(&bounds.upper) {
+        if x < lo || x > hi {
+            return None;
+        }
+        extra.push(x - lo);
+    }
+
+    if bounds.is_loose() {
+        return if extra.is_empty() {
+            Some(0)
+        } else {
+            Multichoose::to_index_with_order( &to_multiset( &extra ), bounds.rest() + 1, Order::Lex )
+        };
+    }
+
+    let table = table( bounds )?;
+    let mut r = bounds.rest();
+    let mut idx = 0;
+
+    for (&x, row) in extra.iter().zip(&table[1..]) {
+        // この部分が x より小さいものが前にあり、その数は row[r-x+1..=r] の和。
+        // 累積和はあふれうるが、差は total 以下なので 2^128 を法として求めてよい
+        let mut prefix = Vec::with_capacity(r + 2);
+        prefix.push(0u128);
+        for &y in row[..r+1].iter() {
+            prefix.push(prefix[prefix.len() - 1].wrapping_add(y));
+        }
+        idx += prefix[r + 1].wrapping_sub(prefix[r + 1 - x]);
+        r -= x;
+    }
+
+    Some(idx)
+}
+
+
+mod streaming {
+    use super::Composition;
+    use crate::count;
+    use crate::streaming::{Source, SourceBack};
+
+    impl Source for Composition {
+        type Item = [usize];
+
+        fn advance(&mut self) {
+            self.step();
+        }
+
+        fn get(&self) -> Option<&[usize]> {
+            self.cursor.current()
+        }
+
+        fn jump(&mut self, k: usize) {
+            self.seek( self.next_index().saturating_add(k as u128) );
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl SourceBack for Composition {
+        fn advance_back(&mut self) {
+            self.step_back();
+        }
+    }
+}
+
+
+mod iterator {
+    use super::Composition;
+    use crate::count;
+    use alloc::vec::Vec;
+    use core::iter::{Iterator, DoubleEndedIterator};
+
+    impl Iterator for Composition {
+        type Item = Vec<usize>;
+
+        fn next(&mut self) -> Option<Vec<usize>> {
+            self.step();
+            self.cursor.front().map(|parts| parts.to_vec())
+        }
+
+        fn nth(&mut self, k: usize) -> Option<Vec<usize>> {
+            self.seek( self.next_index().saturating_add(k as u128) );
+            self.next()
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl DoubleEndedIterator for Composition {
+        fn next_back(&mut self) -> Option<Vec<usize>> {
+            self.step_back();
+            self.cursor.back().map(|parts| parts.to_vec())
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::composition::{to_multiset, from_multiset};
+    use core::ops::RangeInclusive;
+
+    /// Compositions by filtering `Sequence`, which has the same order.
+    fn filtered( n: usize, bounds: &[RangeInclusive<usize>] ) -> Vec<Vec<usize>> {
+        let radices: Vec<usize> = bounds.iter().map(|b| b.end().min(&n) + 1).collect();
+        Sequence::with_radices( &radices )
+            .filter(|seq| seq.iter().sum::<usize>() == n && seq.iter().zip(bounds).all(|(x, b)| b.contains(x)))
+            .collect()
+    }
+
+    fn bounds_list() -> Vec<Vec<RangeInclusive<usize>>> {
+        vec![
+            vec![],
+            vec![ 2..=4 ],
+            vec![ 0..=2, 1..=3, 0..=9 ],
+            vec![ 1..=1, 0..=3, 2..=5, 0..=2 ],
+            vec![ RangeInclusive::new( 3, 1 ), 0..=9 ],
+            vec![ 0..=9, 0..=9, 4..=9, 0..=0 ],
+        ]
+    }
+
+    #[test]
+    fn detail() {
+        for n in 0..9 {
+            for k in 0..5 {
+                let expected = filtered( n, &vec![ 1..=n; k ] );
+                let iter = Composition::new( n, k );
+                assert_eq!( iter.size_hint(), (expected.len(), Some(expected.len())) );
+                assert_eq!( iter.collect::<Vec<_>>(), expected );
+
+                let expected = filtered( n, &vec![ 0..=n; k ] );
+                let iter = Composition::weak( n, k );
+                assert_eq!( iter.size_hint(), (expected.len(), Some(expected.len())) );
+                assert_eq!( iter.collect::<Vec<_>>(), expected );
+            }
+
+            for bounds in bounds_list() {
+                let expected = filtered( n, &bounds );
+                let iter = Composition::with_bounds( n, &bounds );
+                assert_eq!( iter.total(), Some(expected.len() as u128) );
+                assert_eq!( iter.collect::<Vec<_>>(), expected, "{} {:?}", n, bounds );
+            }
+        }
+
+        assert_eq!( Composition::new( 0, 0 ).collect::<Vec<_>>(), vec![ vec![] ] );
+        assert_eq!( Composition::weak( 3, 0 ).next(), None );
+        assert_eq!( Composition::weak( 3, 1 ).collect::<Vec<_>>(), vec![ vec![3] ] );
+        assert_eq!( Composition::weak( 1000, 30 ).total(), count::weak_composition_u128( 1000, 30 ) );
+
+        let iter = Composition::weak( 100, 20 );
+        assert_eq!( iter.remaining(), Some(4910371215196105953021) );
+        assert_eq!( iter.size_hint(), (usize::MAX, None) );
+    }
+
+
+    #[test]
+    fn from_index() {
+        for n in [ 0, 1, 7 ] {
+            for (idx, parts) in Composition::new( n, 3 ).enumerate() {
+                assert_eq!( Composition::from_index( n, 3, idx ).unwrap(), parts );
+                assert_eq!( Composition::to_index( &parts ), Some(idx) );
+            }
+            for (idx, parts) in Composition::weak( n, 3 ).enumerate() {
+                assert_eq!( Composition::from_index_weak( n, 3, idx ).unwrap(), parts );
+                assert_eq!( Composition::to_index_weak( &parts ), Some(idx) );
+            }
+        }
+
+        for bounds in bounds_list() {
+            let iter = Composition::with_bounds( 6, &bounds );
+            let total = iter.total().unwrap();
+            for (idx, parts) in iter.enumerate() {
+                assert_eq!( Composition::from_index_with_bounds( 6, &bounds, idx as u128 ).unwrap(), parts );
+                assert_eq!( Composition::to_index_with_bounds( &parts, &bounds ), Some(idx as u128) );
+            }
+            assert_eq!( Composition::from_index_with_bounds( 6, &bounds, total ), None );
+        }
+
+        assert_eq!( Composition::from_index( 7, 3, 15 ), None );
+        assert_eq!( Composition::to_index( &[2,0,1] ), None );
+        assert_eq!( Composition::to_index_weak( &[2,0,1] ), Some(7) );
+        assert_eq!( Composition::to_index_with_bounds( &[2,0,1], &[ 0..=1, 0..=5, 0..=5 ] ), None );
+        assert_eq!( Composition::to_index_with_bounds( &[2,0], &[ 0..=5, 0..=5, 0..=5 ] ), None );
+
+        // 上限があっても n が大きければ表で求まる
+        let bounds = vec![ 0..=300_000, 10..=20, 0..=500_000 ];
+        let total = Composition::with_bounds( 400_000, &bounds ).total().unwrap();
+        assert_eq!( total, 300_001 * 11 );
+        let parts = Composition::from_index_with_bounds( 400_000, &bounds, 12345 ).unwrap();
+        assert_eq!( parts, vec![ 1122, 13, 400_000 - 1122 - 13 ] );
+        assert_eq!( Composition::to_index_with_bounds( &parts, &bounds ), Some(12345) );
+
+        // 表の行の累積和が u128 からあふれても、total に収まれば番号は求まる
+        let mut bounds = vec![ 0..=255; 29 ];
+        bounds[0] = 0..=1;
+        let total = Composition::with_bounds( 255, &bounds ).total().unwrap();
+        assert_eq!( total, 69364104796177970558856907834039573376 );
+        let mut parts = vec![ 0; 29 ];
+        parts[0] = 1;
+        parts[1] = 254;
+        assert_eq!( Composition::from_index_with_bounds( 255, &bounds, total - 1 ).unwrap(), parts );
+        assert_eq!( Composition::to_index_with_bounds( &parts, &bounds ), Some(total - 1) );
+    }
+
+
+    #[test]
+    fn next_back() {
+        for bounds in bounds_list().into_iter().chain([ vec![ 1..=8; 4 ], vec![ 0..=8; 4 ] ]) {
+            let iter = Composition::with_bounds( 8, &bounds );
+            let forward: Vec<Vec<usize>> = iter.clone().collect();
+            let mut backward: Vec<Vec<usize>> = iter.clone().rev().collect();
+            backward.reverse();
+            assert_eq!( forward, backward );
+
+            // 両端から交互に取り出す
+            let mut iter = iter;
+            let (mut front, mut back) = (Vec::new(), Vec::new());
+            while let Some(x) = iter.next() {
+                front.push(x);
+                if let Some(y) = iter.next_back() {
+                    back.push(y);
+                }
+            }
+            back.reverse();
+            front.extend(back);
+            assert_eq!( front, forward );
+        }
+    }
+
+
+    #[test]
+    fn seek() {
+        let bounds = [ 0..=3, 1..=4, 0..=9, 2..=3 ];
+        let all: Vec<Vec<usize>> = Composition::with_bounds( 9, &bounds ).collect();
+        let mut iter = Composition::with_bounds( 9, &bounds );
+        iter.seek( 20 );
+        assert_eq!( iter.next_index(), 20 );
+        assert_eq!( iter.next().unwrap(), all[20] );
+        assert_eq!( iter.nth( 3 ).unwrap(), all[24] );
+
+        let mut stream = Composition::with_bounds( 9, &bounds ).streaming();
+        assert_eq!( stream.nth( 3 ).unwrap(), &all[3][..] );
+        assert_eq!( stream.next_back().unwrap(), &all[all.len() - 1][..] );
+
+        let mut count = 0;
+        let _ = Composition::with_bounds( 9, &bounds ).for_each_slice(|parts| {
+            assert_eq!( parts, &all[count][..] );
+            count += 1;
+            core::ops::ControlFlow::<()>::Continue(())
+        });
+        assert_eq!( count, all.len() );
+    }
+
+
+    #[test]
+    fn multiset() {
+        let mut multisets = Multichoose::new( 3, 6 );
+        for parts in Composition::weak( 5, 4 ) {
+            let multiset = to_multiset( &parts );
+            assert_eq!( multiset, multisets.next().unwrap() );
+            assert_eq!( from_multiset( &multiset, 5 ).unwrap(), parts );
+        }
+        assert_eq!( multisets.next(), None );
+
+        assert_eq!( to_multiset( &[4] ), vec![] );
+        assert_eq!( from_multiset( &[], 4 ), Some(vec![4]) );
+        assert_eq!( from_multiset( &[2,1], 4 ), None );
+        assert_eq!( from_multiset( &[2,5], 4 ), None );
+    }
+}
@@ -0,0 +1,607 @@
+//! Numbers of permutations, derangements, combinations, sequences, multisets, set partitions,
+//! integer partitions and compositions.
+//!
+//! Every function comes in three flavors:
+//!
+//! - `binomial` etc. return `u64`, or `None` if the number does not fit in it.
+//! - `binomial_u128` etc. return `u128`, or `None` if the number does not fit in it.
+//! - `big::binomial` etc. return `num_bigint::BigUint` (requires the `bigint` feature).
+//!
+//! ```
+//! use enumcombinatorics::count;
+//!
+//! assert_eq!( count::binomial( 5, 2 ), Some(10) );
+//! assert_eq!( count::factorial( 30 ), None );
+//! assert_eq!( count::factorial_u128( 30 ), Some(265252859812191058636308480000000) );
+//! ```
+
+
+use core::convert::TryFrom;
+
+
+/// `n!`.
+pub fn factorial( n: usize ) -> Option<u64> {
+    u64::try_from( factorial_u128( n )? ).ok()
+}
+
+/// `n * (n-1) * ... * (n-k+1)`, the number of `Permutation`s.
+pub fn falling_factorial( n: usize, k: usize ) -> Option<u64> {
+    u64::try_from( falling_factorial_u128( n, k )? ).ok()
+}
+
+/// `n! / (k! (n-k)!)`, the number of `Combination`s.
+pub fn binomial( n: usize, k: usize ) -> Option<u64> {
+    u64::try_from( binomial_u128( n, k )? ).ok()
+}
+
+/// `binomial(n+k-1, k)`, the number of `Multichoose`s.
+pub fn multichoose( n: usize, k: usize ) -> Option<u64> {
+    u64::try_from( multichoose_u128( n, k )? ).ok()
+}
+
+/// `n^k`, the number of `Sequence`s.
+pub fn power( n: usize, k: usize ) -> Option<u64> {
+    u64::try_from( power_u128( n, k )? ).ok()
+}
+
+/// Product of `radices`, the number of `Sequence`s with the mixed radices.
+pub fn radix_product( radices: &[usize] ) -> Option<u64> {
+    u64::try_from( radix_product_u128( radices )? ).ok()
+}
+
+/// Stirling number of the second kind, the number of `SetPartition`s of `n` elements into `k` blocks.
+pub fn stirling2( n: usize, k: usize ) -> Option<u64> {
+    u64::try_from( stirling2_u128( n, k )? ).ok()
+}
+
+/// Bell number, the number of all `SetPartition`s of `n` elements.
+pub fn bell( n: usize ) -> Option<u64> {
+    u64::try_from( bell_u128( n )? ).ok()
+}
+
+/// `binomial(n-1, k-1)`, the number of `Composition`s of `n` into `k` positive parts.
+pub fn composition( n: usize, k: usize ) -> Option<u64> {
+    u64::try_from( composition_u128( n, k )? ).ok()
+}
+
+/// `binomial(n+k-1, n)`, the number of weak `Composition`s of `n` into `k` parts.
+pub fn weak_composition( n: usize, k: usize ) -> Option<u64> {
+    u64::try_from( weak_composition_u128( n, k )? ).ok()
+}
+
+/// `!n`, the number of derangements (`permutation::Derangement`s) of `n` elements.
+pub fn subfactorial( n: usize ) -> Option<u64> {
+    u64::try_from( subfactorial_u128( n )? ).ok()
+}
+
+/// `binomial(n, r) * !(n-r)`, the number of permutations of `n` elements with exactly `r` fixed points.
+pub fn rencontres( n: usize, r: usize ) -> Option<u64> {
+    u64::try_from( rencontres_u128( n, r )? ).ok()
+}
+
+/// `p(n)`, the number of all `IntegerPartition`s of `n`.
+#[cfg(feature = "alloc")]
+pub fn partition( n: usize ) -> Option<u64> {
+    u64::try_from( partition_u128( n )? ).ok()
+}
+
+/// The number of `IntegerPartition`s of `n` into exactly `k` parts.
+#[cfg(feature = "alloc")]
+pub fn partition_into( n: usize, k: usize ) -> Option<u64> {
+    u64::try_from( partition_into_u128( n, k )? ).ok()
+}
+
+/// The number of `IntegerPartition`s of `n` into distinct parts.
+#[cfg(feature = "alloc")]
+pub fn distinct_partition( n: usize ) -> Option<u64> {
+    u64::try_from( distinct_partition_u128( n )? ).ok()
+}
+
+
+/// `n!`.
+pub fn factorial_u128( n: usize ) -> Option<u128> {
+    falling_factorial_u128( n, n )
+}
+
+/// `n * (n-1) * ... * (n-k+1)`, the number of `Permutation`s.
+pub fn falling_factorial_u128( n: usize, k: usize ) -> Option<u128> {
+    if k > n {
+        return Some(0);
+    }
+
+    (n-k+1..n+1).try_fold(1u128, |prod, i| prod.checked_mul(i as u128))
+}
+
+/// `n! / (k! (n-k)!)`, the number of `Combination`s.
+pub fn binomial_u128( n: usize, k: usize ) -> Option<u128> {
+    if k > n {
+        return Some(0);
+    }
+
+    binomial_wide( n as u128, k.min(n - k) )
+}
+
+/// `binomial(n, k)` for `n` which may not fit in `usize`, where `k <= n - k`.
+fn binomial_wide( n: u128, k: usize ) -> Option<u128> {
+    // c * (n-i) は i+1 で割り切れるので、先に約分しておけば途中であふれない
+    (0..k).try_fold(1u128, |c, i| {
+        let g = gcd( c, (i + 1) as u128 );
+        (c / g).checked_mul((n - i as u128) / ((i + 1) as u128 / g))
+    })
+}
+
+/// `binomial(n+k-1, k)`, the number of `Multichoose`s.
+pub fn multichoose_u128( n: usize, k: usize ) -> Option<u128> {
+    if n == 0 {
+        return Some(if k == 0 { 1 } else { 0 });
+    }
+
+    // n+k-1 は usize に収まらないことがある
+    binomial_wide( n as u128 + k as u128 - 1, k.min(n - 1) )
+}
+
+/// `n^k`, the number of `Sequence`s.
+pub fn power_u128( n: usize, k: usize ) -> Option<u128> {
+    (0..k).try_fold(1u128, |prod, _| prod.checked_mul(n as u128))
+}
+
+/// Product of `radices`, the number of `Sequence`s with the mixed radices.
+pub fn radix_product_u128( radices: &[usize] ) -> Option<u128> {
+    radices.iter().try_fold(1u128, |prod, &r| prod.checked_mul(r as u128))
+}
+
+
+/// Stirling number of the second kind, the number of `SetPartition`s of `n` elements into `k` blocks.
+pub fn stirling2_u128( n: usize, k: usize ) -> Option<u128> {
+    if k > n || (k == 0 && n > 0) {
+        return Some(0);
+    }
+    if k <= 1 || k == n {
+        return Some(1);
+    }
+
+    // S(n,k) >= k^(n-k) と S(n,k) >= binomial(n, k-1) より、k と n-k がともに大きければあふれる
+    const MAX: usize = 128;
+    let d = n - k;
+    if k.min(d) > MAX {
+        return None;
+    }
+
+    let mut row = [0u128; MAX + 1];
+    row[0] = 1;
+    if k <= d {
+        // S(n,k) に必要な S(i,j) (j <= k, i-j <= d) だけを漸化式で求める。
+        // それらは S(n,k) 以下なので、途中であふれれば結果もあふれる
+        // row[j] = S(i,j)
+        for i in 1..n+1 {
+            for j in (i.saturating_sub(d).max(1)..i.min(k)+1).rev() {
+                row[j] = row[j].checked_mul(j as u128)?.checked_add(row[j-1])?;
+            }
+            row[0] = 0;
+        }
+        Some(row[k])
+    } else {
+        // n について回すと n が大きいとき終わらないので、2 階の Euler 数 <<d,j>> を使って
+        // S(n,n-d) = Σ_j <<d,j>> binomial(n+d-1-j, 2d) と求める。
+        // <<i,j>> は i について増えるので、途中であふれれば結果もあふれる
+        // row[j] = <<i,j>>
+        for i in 1..d+1 {
+            for j in (1..i).rev() {
+                row[j] = row[j].checked_mul((j + 1) as u128)?.checked_add(row[j-1].checked_mul((2*i - 1 - j) as u128)?)?;
+            }
+        }
+        (0..d).try_fold(0u128, |sum, j| {
+            // n > 2d なので top > 2d
+            let top = n as u128 + (d - 1 - j) as u128;
+            let c = binomial_wide( top, (top - 2 * d as u128).min(2 * d as u128) as usize )?;
+            sum.checked_add(row[j].checked_mul(c)?)
+        })
+    }
+}
+
+/// Bell number, the number of all `SetPartition`s of `n` elements.
+pub fn bell_u128( n: usize ) -> Option<u128> {
+    // B(n) >= S(n,2) = 2^(n-1) - 1
+    if n > 129 {
+        return None;
+    }
+
+    (0..n+1).try_fold(0u128, |sum, k| sum.checked_add(stirling2_u128( n, k )?))
+}
+
+/// `binomial(n-1, k-1)`, the number of `Composition`s of `n` into `k` positive parts.
+pub fn composition_u128( n: usize, k: usize ) -> Option<u128> {
+    if k == 0 || n < k {
+        return Some(if n == k { 1 } else { 0 });
+    }
+
+    binomial_u128( n - 1, k - 1 )
+}
+
+/// `binomial(n+k-1, n)`, the number of weak `Composition`s of `n` into `k` parts.
+pub fn weak_composition_u128( n: usize, k: usize ) -> Option<u128> {
+    multichoose_u128( k, n )
+}
+
+/// `!n`, the number of derangements (`permutation::Derangement`s) of `n` elements.
+pub fn subfactorial_u128( n: usize ) -> Option<u128> {
+    // !k = k !(k-1) + (-1)^k
+    (1..n+1).try_fold(1u128, |d, k| {
+        let d = d.checked_mul(k as u128)?;
+        if k & 1 == 0 { d.checked_add(1) } else { Some(d - 1) }
+    })
+}
+
+/// `binomial(n, r) * !(n-r)`, the number of permutations of `n` elements with exactly `r` fixed points.
+pub fn rencontres_u128( n: usize, r: usize ) -> Option<u128> {
+    if r > n || n - r == 1 {
+        return Some(0);
+    }
+
+    subfactorial_u128( n - r )?.checked_mul(binomial_u128( n, r )?)
+}
+
+
+/// `p(n)`, the number of all `IntegerPartition`s of `n`.
+#[cfg(feature = "alloc")]
+pub fn partition_u128( n: usize ) -> Option<u128> {
+    bounded_partition_u128( n, n )
+}
+
+/// The number of `IntegerPartition`s of `n` into exactly `k` parts.
+///
+/// This takes `O(n k)` time and `O(n)` memory.
+#[cfg(feature = "alloc")]
+pub fn partition_into_u128( n: usize, k: usize ) -> Option<u128> {
+    if k > n || (k == 0 && n > 0) {
+        return Some(0);
+    }
+
+    // 各部分から 1 を引くと、n-k の k 以下の数への分割になる
+    bounded_partition_u128( n - k, k )
+}
+
+/// The number of `IntegerPartition`s of `n` into distinct parts.
+#[cfg(feature = "alloc")]
+pub fn distinct_partition_u128( n: usize ) -> Option<u128> {
+    // q(2822) からあふれる
+    if n > 2821 {
+        return None;
+    }
+
+    // dp[s] = 使った数が相異なる s の分割の数。大きい方から更新して同じ数を 2 度使わない
+    let mut dp = alloc::vec![ 0u128; n + 1 ];
+    dp[0] = 1;
+    for part in 1..n+1 {
+        for s in (part..n+1).rev() {
+            dp[s] = dp[s].checked_add(dp[s - part])?;
+        }
+    }
+    Some(dp[n])
+}
+
+/// The number of partitions of `n` into parts at most `b`.
+///
+/// Every number computed on the way is at most the result, so it overflows only if the result does.
+#[cfg(feature = "alloc")]
+pub(crate) fn bounded_partition_u128( n: usize, b: usize ) -> Option<u128> {
+    // 1 を足して n の分割にできるので p(min(b,n)) 個以上あり、p(1459) からあふれる
+    if b.min(n) > 1458 {
+        return None;
+    }
+
+    let mut dp = alloc::vec![ 0u128; n + 1 ];
+    dp[0] = 1;
+    for part in 1..b.min(n)+1 {
+        for s in part..n+1 {
+            dp[s] = dp[s].checked_add(dp[s - part])?;
+        }
+    }
+    Some(dp[n])
+}
+
+
+fn gcd( a: u128, b: u128 ) -> u128 {
+    if b == 0 { a } else { gcd( b, a % b ) }
+}
+
+/// Converts the number of remaining items into `Iterator::size_hint`.
+pub(crate) fn size_hint( rest: Option<u128> ) -> (usize, Option<usize>) {
+    match rest {
+        Some(rest) if rest <= usize::MAX as u128 => (rest as usize, Some(rest as usize)),
+        _ => (usize::MAX, None),
+    }
+}
+
+
+/// Arbitrary-precision versions via the `num-bigint` crate.
+#[cfg(feature = "bigint")]
+pub mod big {
+    use num_bigint::BigUint;
+    use alloc::vec;
+
+    /// `n!`.
+    pub fn factorial( n: usize ) -> BigUint {
+        falling_factorial( n, n )
+    }
+
+    /// `n * (n-1) * ... * (n-k+1)`, the number of `Permutation`s.
+    pub fn falling_factorial( n: usize, k: usize ) -> BigUint {
+        if k > n {
+            return BigUint::from(0u32);
+        }
+
+        (n-k+1..n+1).fold(BigUint::from(1u32), |prod, i| prod * i)
+    }
+
+    /// `n! / (k! (n-k)!)`, the number of `Combination`s.
+    pub fn binomial( n: usize, k: usize ) -> BigUint {
+        if k > n {
+            return BigUint::from(0u32);
+        }
+
+        binomial_wide( n as u128, k.min(n - k) )
+    }
+
+    /// `binomial(n, k)` for `n` which may not fit in `usize`, where `k <= n - k`.
+    fn binomial_wide( n: u128, k: usize ) -> BigUint {
+        (0..k).fold(BigUint::from(1u32), |c, i| c * (n - i as u128) / (i + 1))
+    }
+
+    /// `binomial(n+k-1, k)`, the number of `Multichoose`s.
+    pub fn multichoose( n: usize, k: usize ) -> BigUint {
+        if n == 0 {
+            return BigUint::from(if k == 0 { 1u32 } else { 0u32 });
+        }
+
+        // n+k-1 は usize に収まらないことがある
+        binomial_wide( n as u128 + k as u128 - 1, k.min(n - 1) )
+    }
+
+    /// `n^k`, the number of `Sequence`s.
+    pub fn power( n: usize, k: usize ) -> BigUint {
+        (0..k).fold(BigUint::from(1u32), |prod, _| prod * n)
+    }
+
+    /// Product of `radices`, the number of `Sequence`s with the mixed radices.
+    pub fn radix_product( radices: &[usize] ) -> BigUint {
+        radices.iter().fold(BigUint::from(1u32), |prod, &r| prod * r)
+    }
+
+    /// Stirling number of the second kind, the number of `SetPartition`s of `n` elements into `k` blocks.
+    pub fn stirling2( n: usize, k: usize ) -> BigUint {
+        if k > n {
+            return BigUint::from(0u32);
+        }
+
+        // row[j] = S(i,j)
+        let mut row = vec![ BigUint::from(0u32); k + 1 ];
+        row[0] = BigUint::from(1u32);
+        for i in 1..n+1 {
+            for j in (1..i.min(k)+1).rev() {
+                row[j] = &row[j] * j + &row[j-1];
+            }
+            row[0] = BigUint::from(0u32);
+        }
+        row.swap_remove(k)
+    }
+
+    /// Bell number, the number of all `SetPartition`s of `n` elements.
+    pub fn bell( n: usize ) -> BigUint {
+        (0..n+1).map(|k| stirling2( n, k )).sum()
+    }
+
+    /// `binomial(n-1, k-1)`, the number of `Composition`s of `n` into `k` positive parts.
+    pub fn composition( n: usize, k: usize ) -> BigUint {
+        if k == 0 || n < k {
+            return BigUint::from(if n == k { 1u32 } else { 0u32 });
+        }
+
+        binomial( n - 1, k - 1 )
+    }
+
+    /// `binomial(n+k-1, n)`, the number of weak `Composition`s of `n` into `k` parts.
+    pub fn weak_composition( n: usize, k: usize ) -> BigUint {
+        multichoose( k, n )
+    }
+
+    /// `!n`, the number of derangements (`permutation::Derangement`s) of `n` elements.
+    pub fn subfactorial( n: usize ) -> BigUint {
+        (1..n+1).fold(BigUint::from(1u32), |d, k| {
+            let d = d * k;
+            if k & 1 == 0 { d + 1u32 } else { d - 1u32 }
+        })
+    }
+
+    /// `binomial(n, r) * !(n-r)`, the number of permutations of `n` elements with exactly `r` fixed points.
+    pub fn rencontres( n: usize, r: usize ) -> BigUint {
+        if r > n {
+            return BigUint::from(0u32);
+        }
+
+        subfactorial( n - r ) * binomial( n, r )
+    }
+
+    /// `p(n)`, the number of all `IntegerPartition`s of `n`.
+    pub fn partition( n: usize ) -> BigUint {
+        bounded_partition( n, n )
+    }
+
+    /// The number of `IntegerPartition`s of `n` into exactly `k` parts.
+    pub fn partition_into( n: usize, k: usize ) -> BigUint {
+        if k > n || (k == 0 && n > 0) {
+            return BigUint::from(0u32);
+        }
+
+        bounded_partition( n - k, k )
+    }
+
+    /// The number of `IntegerPartition`s of `n` into distinct parts.
+    pub fn distinct_partition( n: usize ) -> BigUint {
+        let mut dp = vec![ BigUint::from(0u32); n + 1 ];
+        dp[0] = BigUint::from(1u32);
+        for part in 1..n+1 {
+            for s in (part..n+1).rev() {
+                dp[s] = &dp[s] + &dp[s - part];
+            }
+        }
+        dp.swap_remove(n)
+    }
+
+    fn bounded_partition( n: usize, b: usize ) -> BigUint {
+        let mut dp = vec![ BigUint::from(0u32); n + 1 ];
+        dp[0] = BigUint::from(1u32);
+        for part in 1..b.min(n)+1 {
+            for s in part..n+1 {
+                dp[s] = &dp[s] + &dp[s - part];
+            }
+        }
+        dp.swap_remove(n)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::count;
+
+    #[test]
+    fn small() {
+        assert_eq!( count::factorial( 0 ), Some(1) );
+        assert_eq!( count::factorial( 5 ), Some(120) );
+        assert_eq!( count::falling_factorial( 5, 2 ), Some(20) );
+        assert_eq!( count::falling_factorial( 2, 5 ), Some(0) );
+        assert_eq!( count::binomial( 5, 2 ), Some(10) );
+        assert_eq!( count::binomial( 2, 5 ), Some(0) );
+        assert_eq!( count::multichoose( 4, 3 ), Some(20) );
+        assert_eq!( count::multichoose( 0, 0 ), Some(1) );
+        assert_eq!( count::multichoose( 0, 3 ), Some(0) );
+        assert_eq!( count::power( 4, 3 ), Some(64) );
+        assert_eq!( count::power( 0, 0 ), Some(1) );
+        assert_eq!( count::radix_product( &[2, 3, 4] ), Some(24) );
+        assert_eq!( count::radix_product( &[] ), Some(1) );
+        assert_eq!( count::radix_product_u128( &[10; 30] ), count::power_u128( 10, 30 ) );
+        assert_eq!( count::radix_product_u128( &[10; 40] ), None );
+        assert_eq!( count::stirling2( 10, 4 ), Some(34105) );
+        assert_eq!( count::stirling2( 10, 5 ), Some(42525) );
+        assert_eq!( count::stirling2( 0, 0 ), Some(1) );
+        assert_eq!( count::stirling2( 3, 0 ), Some(0) );
+        assert_eq!( count::stirling2( 3, 4 ), Some(0) );
+        assert_eq!( count::bell( 0 ), Some(1) );
+        assert_eq!( count::bell( 10 ), Some(115975) );
+        assert_eq!( count::composition( 5, 3 ), Some(6) );
+        assert_eq!( count::composition( 0, 0 ), Some(1) );
+        assert_eq!( count::composition( 2, 3 ), Some(0) );
+        assert_eq!( count::weak_composition( 2, 3 ), Some(6) );
+        assert_eq!( count::weak_composition( 0, 0 ), Some(1) );
+        assert_eq!( count::weak_composition( 3, 0 ), Some(0) );
+        assert_eq!( count::subfactorial( 0 ), Some(1) );
+        assert_eq!( count::subfactorial( 1 ), Some(0) );
+        assert_eq!( count::subfactorial( 5 ), Some(44) );
+        assert_eq!( count::rencontres( 5, 2 ), Some(20) );
+        assert_eq!( count::rencontres( 5, 4 ), Some(0) );
+        assert_eq!( count::rencontres( 5, 5 ), Some(1) );
+        assert_eq!( count::rencontres( 5, 6 ), Some(0) );
+    }
+
+
+    #[test]
+    fn overflow() {
+        assert_eq!( count::factorial( 20 ), Some(2432902008176640000) );
+        assert_eq!( count::factorial( 21 ), None );
+        assert_eq!( count::factorial_u128( 21 ), Some(51090942171709440000) );
+        assert_eq!( count::factorial_u128( 35 ), None );
+
+        // 途中の値があふれても結果が収まれば求まる
+        assert_eq!( count::binomial( 67, 33 ), Some(14226520737620288370) );
+        assert_eq!( count::binomial( 68, 34 ), None );
+        assert_eq!( count::binomial_u128( 130, 65 ), Some(95067625827960698145584333020095113100) );
+        assert_eq!( count::binomial_u128( 132, 66 ), None );
+
+        assert_eq!( count::bell( 25 ), Some(4638590332229999353) );
+        assert_eq!( count::bell( 26 ), None );
+        // S(n,n-1) = binomial(n,2), S(n,n-2) = binomial(n,3) + 3 binomial(n,4)
+        assert_eq!( count::stirling2( 1000, 999 ), Some(499500) );
+        assert_eq!( count::stirling2( 1000, 998 ), Some(124417541250) );
+        assert_eq!( count::stirling2_u128( usize::MAX, 1 ), Some(1) );
+        let m = usize::MAX as u128;
+        assert_eq!( count::stirling2_u128( usize::MAX, usize::MAX - 1 ), Some(m * (m - 1) / 2) );
+        assert_eq!( count::stirling2_u128( usize::MAX, usize::MAX - 2 ), None );
+        assert_eq!( count::stirling2_u128( 1000, 500 ), None );
+
+        assert_eq!( count::subfactorial( 20 ), Some(895014631192902121) );
+        assert_eq!( count::subfactorial( 21 ), None );
+        assert_eq!( count::subfactorial_u128( 34 ), Some(108610077126170304674801654684367969729) );
+        assert_eq!( count::subfactorial_u128( 35 ), None );
+        assert_eq!( count::rencontres_u128( 1000, 998 ), Some(499500) );
+        assert_eq!( count::rencontres_u128( usize::MAX, usize::MAX - 1 ), Some(0) );
+    }
+
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn partition() {
+        assert_eq!( count::partition( 0 ), Some(1) );
+        assert_eq!( count::partition( 100 ), Some(190569292) );
+        assert_eq!( count::partition_into( 10, 3 ), Some(8) );
+        assert_eq!( count::partition_into( 3, 0 ), Some(0) );
+        assert_eq!( count::distinct_partition( 100 ), Some(444793) );
+
+        assert!( count::partition( 416 ).is_some() );
+        assert_eq!( count::partition( 417 ), None );
+        assert!( count::partition_u128( 1458 ).is_some() );
+        assert_eq!( count::partition_u128( 1459 ), None );
+        assert_eq!( count::partition_into_u128( 1_000_000, 2 ), Some(500000) );
+        assert!( count::distinct_partition_u128( 2821 ).is_some() );
+        assert_eq!( count::distinct_partition_u128( 2822 ), None );
+
+        // n+k-1 が usize に収まらなくても数えられる
+        let m = usize::MAX as u128;
+        assert_eq!( count::multichoose_u128( usize::MAX, 2 ), Some(m * (m + 1) / 2) );
+        assert_eq!( count::multichoose_u128( usize::MAX, 3 ), None );
+        assert_eq!( count::multichoose_u128( 2, usize::MAX ), Some(m + 1) );
+        assert_eq!( count::weak_composition_u128( usize::MAX, 2 ), Some(m + 1) );
+    }
+
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn big() {
+        use num_bigint::BigUint;
+
+        assert_eq!( count::big::binomial( 132, 66 ).to_string(), "377389666165540953244592352291892721700" );
+        assert_eq!( count::big::factorial( 35 ), count::big::falling_factorial( 35, 35 ) );
+        assert_eq!( count::big::multichoose( 4, 3 ), 20u32.into() );
+        assert_eq!( count::big::multichoose( usize::MAX, 2 ), BigUint::from(usize::MAX) * (BigUint::from(usize::MAX) + 1u32) / 2u32 );
+        assert_eq!( count::big::composition( 5, 3 ), 6u32.into() );
+        assert_eq!( count::big::weak_composition( 2, 3 ), 6u32.into() );
+        for n in 0..40 {
+            let d = count::big::subfactorial( n );
+            assert_eq!( count::subfactorial_u128( n ).map(BigUint::from), Some(d).filter(|d| d.bits() <= 128) );
+            assert_eq!( count::big::rencontres( n, 3 ), count::big::subfactorial( n.saturating_sub(3) ) * count::big::binomial( n, 3 ) );
+        }
+        assert_eq!( count::big::power( 10, 40 ).to_string(), format!("1{}", "0".repeat(40)) );
+        assert_eq!( count::big::radix_product( &[10; 40] ), count::big::power( 10, 40 ) );
+
+        for n in 0..50 {
+            let bell = count::big::bell( n );
+            assert_eq!( count::bell_u128( n ).map(BigUint::from), Some(bell).filter(|b| b.bits() <= 128) );
+            for k in 0..n+1 {
+                let s = count::big::stirling2( n, k );
+                assert_eq!( count::stirling2_u128( n, k ).map(BigUint::from), Some(s).filter(|s| s.bits() <= 128) );
+            }
+        }
+        for n in [ 100, 300 ] {
+            for d in 1..12 {
+                let s = count::big::stirling2( n, n - d );
+                assert_eq!( count::stirling2_u128( n, n - d ).map(BigUint::from), Some(s).filter(|s| s.bits() <= 128) );
+            }
+        }
+
+        for n in 0..60 {
+            assert_eq!( BigUint::from( count::partition_u128( n ).unwrap() ), count::big::partition( n ) );
+            assert_eq!( BigUint::from( count::distinct_partition_u128( n ).unwrap() ), count::big::distinct_partition( n ) );
+            assert_eq!( BigUint::from( count::partition_into_u128( n, 7 ).unwrap() ), count::big::partition_into( n, 7 ) );
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Bookkeeping shared by the iterators, which can be advanced from both ends.
+
+
+use core::ops::ControlFlow;
+use alloc::vec::Vec;
+
+
+/// One end of a `Cursor`.
+#[derive(Debug, Clone)]
+struct End {
+    state: Vec<usize>,
+    /// Whether `state` has already been yielded. Otherwise it is the next item
+    /// (for the front) or has not been built yet (for the back).
+    yielded: bool,
+    /// Whether the last step reached an item.
+    ok: bool,
+    /// Number of items taken from this end.
+    taken: u128,
+}
+
+
+/// A pair of states walking towards each other.
+///
+/// The owner knows how to step a state forward or backward, and `Cursor` keeps track of
+/// where both ends are and when they meet. If the total number of items does not fit
+/// in `u128`, the ends are assumed never to meet.
+#[derive(Debug, Clone)]
+pub(crate) struct Cursor {
+    front: End,
+    back: End,
+    from_back: bool,
+    /// Whether `seek` moved past the last item without `total` to tell where it is.
+    exhausted: bool,
+    /// Length of the items; the states may carry extra data after them.
+    /// If `None`, the items vary in length, which the last number of the states holds.
+    dim: Option<usize>,
+    total: Option<u128>,
+}
+
+impl Cursor {
+    /// `first` is the first item, which must be valid unless `total` is zero.
+    pub(crate) fn new( first: Vec<usize>, dim: usize, total: Option<u128> ) -> Cursor {
+        Cursor {
+            front: End { state: first, yielded: false, ok: false, taken: 0 },
+            // 後ろから進めるまでは確保しない
+            back: End { state: Vec::new(), yielded: false, ok: false, taken: 0 },
+            from_back: false,
+            exhausted: false,
+            dim: Some(dim),
+            total,
+        }
+    }
+
+    /// Same as `Cursor::new`, but for items of varying lengths: the last number of each state
+    /// is the length of the item, which the state holds at its beginning.
+    pub(crate) fn with_varying_len( first: Vec<usize>, total: Option<u128> ) -> Cursor {
+        Cursor { dim: None, ..Cursor::new( first, 0, total ) }
+    }
+
+    /// Number of items not yet taken from either end.
+    pub(crate) fn remaining(&self) -> Option<u128> {
+        self.total.map(|total| total - self.front.taken - self.back.taken)
+    }
+
+    /// Position of the next item from the front, counting from 0.
+    pub(crate) fn next_index(&self) -> u128 {
+        self.front.taken
+    }
+
+    /// Moves the front to the next item. `next` rearranges a state into the following one,
+    /// or returns `false` if it is the last one.
+    pub(crate) fn step<F>( &mut self, next: F )
+        where F: FnOnce(&mut [usize]) -> bool
+    {
+        self.from_back = false;
+        self.front.ok = false;
+
+        if self.exhausted || self.remaining() == Some(0) || (self.front.yielded && !next(&mut self.front.state)) {
+            return;
+        }
+
+        self.front.yielded = true;
+        self.front.ok = true;
+        self.front.taken += 1;
+    }
+
+    /// Moves the front through the remaining items and calls `f` on each of them, until it returns
+    /// `Break`. `next` is the same as in `step`.
+    pub(crate) fn for_each<B, N, F>( &mut self, mut next: N, mut f: F ) -> ControlFlow<B>
+        where N: FnMut(&mut [usize]) -> bool, F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        self.from_back = false;
+
+        // 1 つずつ remaining() を計算し直さずに済むよう、残りの数を数えておく
+        let mut rest = if self.exhausted { Some(0) } else { self.remaining() };
+        while rest != Some(0) {
+            if self.front.yielded && !next(&mut self.front.state) {
+                break;
+            }
+            self.front.yielded = true;
+            self.front.ok = true;
+            self.front.taken += 1;
+            rest = rest.map(|rest| rest - 1);
+
+            if let ControlFlow::Break(b) = f(item( self.dim, &self.front.state )) {
+                return ControlFlow::Break(b);
+            }
+        }
+
+        self.front.ok = false;
+        ControlFlow::Continue(())
+    }
+
+    /// Moves the back to the previous item. `last` returns the last item,
+    /// and `prev` rearranges a state into the preceding one, or returns `false` if it is the first one.
+    pub(crate) fn step_back<L, F>( &mut self, last: L, prev: F )
+        where L: FnOnce() -> Vec<usize>, F: FnOnce(&mut [usize]) -> bool
+    {
+        self.from_back = true;
+        self.back.ok = false;
+
+        if self.exhausted || self.remaining() == Some(0) {
+            return;
+        }
+        if !self.back.yielded {
+            self.back.state = last();
+        } else if !prev(&mut self.back.state) {
+            return;
+        }
+
+        self.back.yielded = true;
+        self.back.ok = true;
+        self.back.taken += 1;
+    }
+
+    /// Moves the front so that the next step reaches the `idx`-th item, counting from 0.
+    /// `state` builds that item; it is called only if the item has not been taken from the back.
+    pub(crate) fn seek<F>( &mut self, idx: u128, state: F )
+        where F: FnOnce() -> Option<Vec<usize>>
+    {
+        self.from_back = false;
+        self.front.ok = false;
+
+        let end = self.total.map(|total| total - self.back.taken);
+        if end.map_or(true, |end| idx < end) {
+            if let Some(state) = state() {
+                self.front.state = state;
+                self.front.yielded = false;
+                self.front.taken = idx;
+                self.exhausted = false;
+                return;
+            }
+        }
+
+        // 残りはない。total が分からなければ front.state は古いままなので、印を付けておく
+        self.exhausted = self.total.is_none();
+        self.front.yielded = true;
+        self.front.taken = end.unwrap_or(idx);
+    }
+
+    /// The item last reached from the front.
+    pub(crate) fn front(&self) -> Option<&[usize]> {
+        if self.front.ok { Some(item( self.dim, &self.front.state )) } else { None }
+    }
+
+    /// The item last reached from the back.
+    pub(crate) fn back(&self) -> Option<&[usize]> {
+        if self.back.ok { Some(item( self.dim, &self.back.state )) } else { None }
+    }
+
+    /// The item last reached from either end.
+    pub(crate) fn current(&self) -> Option<&[usize]> {
+        if self.from_back { self.back() } else { self.front() }
+    }
+}
+
+
+/// The item which `state` holds.
+fn item( dim: Option<usize>, state: &[usize] ) -> &[usize] {
+    let len = dim.unwrap_or_else(|| state[state.len() - 1]);
+    &state[..len]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Cursor;
+    use alloc::vec;
+
+    #[test]
+    fn seek_past_end() {
+        // 0..3 を数えるが、total は分からないことにする
+        let next = |state: &mut [usize]| { state[0] += 1; state[0] < 3 };
+
+        let mut cursor = Cursor::new( vec![0], 1, None );
+        cursor.step( next );
+        assert_eq!( cursor.front(), Some(&[0][..]) );
+
+        cursor.seek( 5, || None );
+        cursor.step( next );
+        assert_eq!( cursor.front(), None );
+        cursor.step_back( || vec![2], |_| false );
+        assert_eq!( cursor.back(), None );
+
+        cursor.seek( 1, || Some(vec![1]) );
+        cursor.step( next );
+        assert_eq!( cursor.front(), Some(&[1][..]) );
+        cursor.step( next );
+        assert_eq!( cursor.front(), Some(&[2][..]) );
+        cursor.step( next );
+        assert_eq!( cursor.front(), None );
+    }
+}
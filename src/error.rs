@@ -0,0 +1,28 @@
+use core::fmt;
+
+
+/// Error returned by the `try_new` constructors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// `dim` is larger than `n`, so no item can be made of distinct numbers.
+    DimTooLarge { dim: usize, n: usize },
+    /// `n` is zero while `dim` is positive, so there is no number to choose.
+    EmptyRange { dim: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result {
+        match *self {
+            Error::DimTooLarge { dim, n } => {
+                write!( f, "cannot choose {} distinct numbers out of {}", dim, n )
+            },
+            Error::EmptyRange { dim } => {
+                write!( f, "cannot choose {} numbers out of none", dim )
+            },
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
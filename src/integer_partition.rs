@@ -0,0 +1,813 @@
+//! Partitions of an integer `n` into positive parts.
+//!
+//! A partition is represented by its parts in non-increasing order, and the partitions are
+//! generated in reverse lexicographic order. `multiplicities()` turns them into the numbers
+//! of times each part appears instead.
+//!
+//! ```
+//! use enumcombinatorics::*;
+//! use enumcombinatorics::integer_partition::Constraint;
+//!
+//! let mut iter = IntegerPartition::new( 4 );
+//!
+//! assert_eq!( iter.next().unwrap(), &[4] );
+//! assert_eq!( iter.next().unwrap(), &[3,1] );
+//! assert_eq!( iter.next().unwrap(), &[2,2] );
+//! assert_eq!( iter.next().unwrap(), &[2,1,1] );
+//! assert_eq!( iter.next().unwrap(), &[1,1,1,1] );
+//! assert_eq!( iter.next(), None );
+//!
+//! let mut iter = IntegerPartition::with_constraint( 6, Constraint::Distinct ).multiplicities();
+//!
+//! assert_eq!( iter.next().unwrap(), &[0,0,0,0,0,1] );
+//! assert_eq!( iter.next().unwrap(), &[1,0,0,0,1,0] );
+//! assert_eq!( iter.next().unwrap(), &[0,1,0,1,0,0] );
+//! assert_eq!( iter.next().unwrap(), &[1,1,1,0,0,0] );
+//! assert_eq!( iter.next(), None );
+//! ```
+
+
+use crate::{count, Streaming};
+use crate::cursor::Cursor;
+use core::convert::TryFrom;
+use core::ops::ControlFlow;
+use alloc::vec;
+use alloc::vec::Vec;
+
+
+/// Which partitions `IntegerPartition` generates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Constraint {
+    /// All partitions.
+    #[default]
+    All,
+    /// Partitions into exactly `k` parts.
+    Parts(usize),
+    /// Partitions into parts at most `m`.
+    MaxPart(usize),
+    /// Partitions into distinct parts.
+    Distinct,
+}
+
+
+#[derive(Debug, Clone)]
+pub struct IntegerPartition {
+    cursor: Cursor,
+    n: usize,
+    constraint: Constraint,
+    bounds: Bounds,
+}
+
+impl IntegerPartition {
+    /// Generates all partitions of `n` in reverse lexicographic order.
+    ///
+    /// There is exactly one (empty) partition if `n == 0`.
+    pub fn new( n: usize ) -> Self {
+        IntegerPartition::with_constraint( n, Constraint::All )
+    }
+
+    /// Same as `IntegerPartition::new`, but generates only the partitions satisfying `constraint`.
+    pub fn with_constraint( n: usize, constraint: Constraint ) -> Self {
+        let bounds = Bounds::new( n, constraint );
+        IntegerPartition {
+            cursor: Cursor::with_varying_len( first( n, bounds ), total( n, constraint ) ),
+            n,
+            constraint,
+            bounds,
+        }
+    }
+
+    /// Constraint on the partitions.
+    pub fn constraint(&self) -> Constraint {
+        self.constraint
+    }
+
+    /// Number of all partitions, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        total( self.n, self.constraint )
+    }
+
+    /// Number of partitions not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.cursor.remaining()
+    }
+
+    /// Returns the `idx`-th partition (counting from 0) in the order of the iterator,
+    /// or `None` if there are not so many partitions.
+    pub fn from_index( n: usize, idx: usize ) -> Option<Vec<usize>> {
+        unrank( n, Constraint::All, idx as u128 )
+    }
+
+    /// Returns the position of `parts` in the order of the iterator, that is, the inverse of
+    /// `IntegerPartition::from_index`.
+    ///
+    /// Returns `None` if `parts` is not a non-increasing sequence of positive numbers
+    /// or the index does not fit in `usize`.
+    pub fn to_index( parts: &[usize] ) -> Option<usize> {
+        usize::try_from( rank( parts, Constraint::All )? ).ok()
+    }
+
+    /// Same as `IntegerPartition::from_index`, for `IntegerPartition::with_constraint( n, constraint )`.
+    ///
+    /// This also returns `None` if the number of partitions does not fit in `u128`.
+    /// It takes `O(n^2)` memory, or `O(n)` for `Constraint::Parts`.
+    pub fn from_index_with_constraint( n: usize, constraint: Constraint, idx: u128 ) -> Option<Vec<usize>> {
+        unrank( n, constraint, idx )
+    }
+
+    /// Same as `IntegerPartition::to_index`, for `IntegerPartition::with_constraint( n, constraint )`
+    /// where `n` is the sum of `parts`.
+    ///
+    /// This also returns `None` if `parts` does not satisfy `constraint`
+    /// or the number of partitions does not fit in `u128`.
+    pub fn to_index_with_constraint( parts: &[usize], constraint: Constraint ) -> Option<u128> {
+        rank( parts, constraint )
+    }
+
+    /// Moves the iterator so that the next call of `next()` returns the `idx`-th partition,
+    /// without generating the ones in between.
+    pub fn seek( &mut self, idx: u128 ) {
+        let (n, constraint, bounds) = (self.n, self.constraint, self.bounds);
+        self.cursor.seek( idx, || {
+            let parts = unrank( n, constraint, idx )?;
+            let mut state = first( n, bounds );
+            let len = state.len() - 1;
+            state[..parts.len()].copy_from_slice( &parts );
+            state[len] = parts.len();
+            Some(state)
+        });
+    }
+
+    /// Position of the partition which the next call of `next()` returns.
+    pub fn next_index(&self) -> u128 {
+        self.cursor.next_index()
+    }
+
+    /// Calls `f` on each remaining partition by reference without allocating, until `f` returns
+    /// `ControlFlow::Break`. The iterator can be resumed after it.
+    pub fn for_each_slice<B, F>( &mut self, f: F ) -> ControlFlow<B>
+        where F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        let bounds = self.bounds;
+        self.cursor.for_each(|state| next_state( state, bounds ), f)
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
+
+    /// Iterator generating the same partitions as multiplicity vectors (see `to_multiplicities`).
+    pub fn multiplicities(self) -> Multiplicities {
+        Multiplicities { inner: self }
+    }
+
+    fn step(&mut self) {
+        let bounds = self.bounds;
+        self.cursor.step(|state| next_state( state, bounds ));
+    }
+
+    fn step_back(&mut self) {
+        let (n, bounds) = (self.n, self.bounds);
+        self.cursor.step_back(|| last( n, bounds ), |state| prev_state( state, bounds ));
+    }
+}
+
+
+/// Converts a partition into its multiplicity vector `m` of length `n`, the sum of `parts`,
+/// where `m[i]` is the number of parts equal to `i+1`.
+///
+/// Returns `None` if `parts` contains 0.
+pub fn to_multiplicities( parts: &[usize] ) -> Option<Vec<usize>> {
+    let n = parts.iter().sum();
+    let mut mult = vec![ 0; n ];
+
+    for &x in parts.iter() {
+        *mult.get_mut( x.checked_sub(1)? )? += 1;
+    }
+
+    Some(mult)
+}
+
+/// Converts a multiplicity vector into the parts in non-increasing order, that is, the inverse of
+/// `to_multiplicities`.
+pub fn from_multiplicities( mult: &[usize] ) -> Vec<usize> {
+    let mut parts = Vec::new();
+
+    for (i, &m) in mult.iter().enumerate().rev() {
+        parts.extend( core::iter::repeat(i + 1).take(m) );
+    }
+
+    parts
+}
+
+
+/// Same as `IntegerPartition::total`.
+fn total( n: usize, constraint: Constraint ) -> Option<u128> {
+    match constraint {
+        Constraint::All => count::partition_u128( n ),
+        Constraint::Parts(k) => count::partition_into_u128( n, k ),
+        Constraint::MaxPart(m) => count::bounded_partition_u128( n, m ),
+        Constraint::Distinct => count::distinct_partition_u128( n ),
+    }
+}
+
+
+/// `Constraint` in the form used to generate and count the partitions.
+#[derive(Debug, Clone, Copy)]
+struct Bounds {
+    /// Number of parts, or `None` for any number.
+    parts: Option<usize>,
+    max_part: usize,
+    distinct: bool,
+}
+
+impl Bounds {
+    fn new( n: usize, constraint: Constraint ) -> Bounds {
+        let bounds = Bounds { parts: None, max_part: n, distinct: false };
+        match constraint {
+            Constraint::All => bounds,
+            Constraint::Parts(k) => Bounds { parts: Some(k), ..bounds },
+            Constraint::MaxPart(m) => Bounds { max_part: m.min(n), ..bounds },
+            Constraint::Distinct => Bounds { distinct: true, ..bounds },
+        }
+    }
+
+    /// Largest part allowed after `p`.
+    fn cap_after( self, p: usize ) -> usize {
+        if self.distinct { p - 1 } else { p }
+    }
+
+    /// Smallest sum of `count` parts (or any number of them).
+    fn min_sum( self, count: Option<usize> ) -> usize {
+        match count {
+            Some(c) if self.distinct => triangular( c ),
+            Some(c) => c,
+            None => 0,
+        }
+    }
+
+    /// Whether `r` can be divided into `count` parts (or any number of them) at most `cap`.
+    fn feasible( self, r: usize, count: Option<usize>, cap: usize ) -> bool {
+        let max_sum = match count {
+            Some(0) => 0,
+            Some(c) if self.distinct => {
+                if c > cap {
+                    return false;
+                }
+                c.saturating_mul(cap) - triangular( c - 1 )
+            },
+            Some(c) => c.saturating_mul(cap),
+            None if self.distinct => triangular( cap ),
+            None => if cap == 0 { 0 } else { usize::MAX },
+        };
+        self.min_sum( count ) <= r && r <= max_sum
+    }
+}
+
+/// `1 + 2 + ... + c`.
+fn triangular( c: usize ) -> usize {
+    c.saturating_mul(c.saturating_add(1)) / 2
+}
+
+
+/// The first partition in reverse lexicographic order.
+///
+/// The state holds the parts followed by unused room, and its last number is the number of parts.
+fn first( n: usize, bounds: Bounds ) -> Vec<usize> {
+    let room = bounds.parts.unwrap_or(n).min(n);
+    let mut state = vec![ 0; room + 1 ];
+    // 分割がなければ中身は使われない
+    if bounds.feasible( n, bounds.parts, bounds.max_part ) {
+        state[room] = fill_largest( &mut state, 0, n, bounds.parts, bounds.max_part, bounds );
+    }
+    state
+}
+
+/// The last partition in reverse lexicographic order.
+fn last( n: usize, bounds: Bounds ) -> Vec<usize> {
+    let room = bounds.parts.unwrap_or(n).min(n);
+    let mut state = vec![ 0; room + 1 ];
+    if bounds.feasible( n, bounds.parts, bounds.max_part ) {
+        state[room] = fill_smallest( &mut state, 0, n, bounds.parts, bounds.max_part, bounds );
+    }
+    state
+}
+
+/// Writes the lexicographically largest partition of `r` into `count` parts at most `cap`
+/// from `state[from]`, and returns where it ends. It must exist.
+fn fill_largest( state: &mut [usize], from: usize, r: usize, count: Option<usize>, cap: usize, bounds: Bounds ) -> usize {
+    let (mut j, mut r, mut count, mut cap) = (from, r, count, cap);
+
+    while r > 0 || count.map_or(false, |c| c > 0) {
+        // 残りの部分に最低限必要な分を除いて、できるだけ大きくする
+        let rest = count.map(|c| c - 1);
+        let p = cap.min(r - bounds.min_sum( rest ));
+        state[j] = p;
+        j += 1;
+        r -= p;
+        cap = bounds.cap_after( p );
+        count = rest;
+    }
+
+    j
+}
+
+/// Same as `fill_largest`, but writes the lexicographically smallest one.
+fn fill_smallest( state: &mut [usize], from: usize, r: usize, count: Option<usize>, cap: usize, bounds: Bounds ) -> usize {
+    let (mut j, mut r, mut count, mut cap) = (from, r, count, cap);
+
+    while r > 0 || count.map_or(false, |c| c > 0) {
+        // 残りを分けきれる範囲で、できるだけ小さくする
+        let rest = count.map(|c| c - 1);
+        let hi = cap.min(r - bounds.min_sum( rest ));
+        let p = smallest( 1, hi, |p| bounds.feasible( r - p, rest, bounds.cap_after( p ) ) );
+        state[j] = p;
+        j += 1;
+        r -= p;
+        cap = bounds.cap_after( p );
+        count = rest;
+    }
+
+    j
+}
+
+/// The smallest `p` in `lo..=hi` such that `f(p)`, where `f` is monotone and `f(hi)` holds.
+fn smallest<F: Fn(usize) -> bool>( lo: usize, hi: usize, f: F ) -> usize {
+    let (mut lo, mut hi) = (lo, hi);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if f(mid) { hi = mid } else { lo = mid + 1 }
+    }
+    lo
+}
+
+/// Rearranges `state` into the next partition in reverse lexicographic order,
+/// or returns `false` if it is the last one.
+fn next_state( state: &mut [usize], bounds: Bounds ) -> bool {
+    let room = state.len() - 1;
+    let len = state[room];
+
+    // 減らせる一番右の部分を探し、残りをできるだけ大きな部分で埋める
+    let mut r = 0;
+    for i in (0..len).rev() {
+        r += state[i];
+        let rest = bounds.parts.map(|k| k - i - 1);
+        let min = bounds.min_sum( rest );
+        if r <= min {
+            continue;
+        }
+
+        let v = (state[i] - 1).min(r - min);
+        if v >= 1 && bounds.feasible( r - v, rest, bounds.cap_after( v ) ) {
+            state[i] = v;
+            state[room] = fill_largest( state, i + 1, r - v, rest, bounds.cap_after( v ), bounds );
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Rearranges `state` into the previous partition in reverse lexicographic order,
+/// or returns `false` if it is the first one.
+fn prev_state( state: &mut [usize], bounds: Bounds ) -> bool {
+    let room = state.len() - 1;
+    let len = state[room];
+
+    // 増やせる一番右の部分を探し、残りをできるだけ小さな部分で埋める
+    let mut r = 0;
+    for i in (0..len).rev() {
+        r += state[i];
+        let rest = bounds.parts.map(|k| k - i - 1);
+        let min = bounds.min_sum( rest );
+        let cap = if i == 0 { bounds.max_part } else { bounds.cap_after( state[i-1] ) };
+        if r < min {
+            continue;
+        }
+
+        let hi = cap.min(r - min);
+        let feasible = |v| bounds.feasible( r - v, rest, bounds.cap_after( v ) );
+        if hi > state[i] && feasible(hi) {
+            let v = smallest( state[i] + 1, hi, feasible );
+            state[i] = v;
+            state[room] = fill_smallest( state, i + 1, r - v, rest, bounds.cap_after( v ), bounds );
+            return true;
+        }
+    }
+
+    false
+}
+
+
+/// Numbers of partitions of the remaining sum, to rank partitions.
+struct Counter {
+    bounds: Bounds,
+    /// For any number of parts, `table[offset(s) + b]` is the number of partitions of `s`
+    /// into parts at most `b <= min(s, max_part)`. Empty for a fixed number of parts.
+    table: Vec<u128>,
+}
+
+impl Counter {
+    /// Returns `None` if some number does not fit in `u128`.
+    fn new( n: usize, bounds: Bounds ) -> Option<Counter> {
+        let mut counter = Counter { bounds, table: Vec::new() };
+        if bounds.parts.is_some() {
+            return Some(counter);
+        }
+
+        counter.table.reserve( counter.offset( n + 1 ) );
+        for s in 0..n+1 {
+            for b in 0..s.min(bounds.max_part)+1 {
+                let x = if s == 0 {
+                    1
+                } else if b == 0 {
+                    0
+                } else {
+                    // 最大の部分が b 未満か、ちょうど b か
+                    counter.table[counter.offset( s ) + b - 1].checked_add(counter.at_most( s - b, None, bounds.cap_after( b ) ))?
+                };
+                counter.table.push(x);
+            }
+        }
+
+        Some(counter)
+    }
+
+    fn offset( &self, s: usize ) -> usize {
+        let b = self.bounds.max_part;
+        if s <= b + 1 { s * (s + 1) / 2 } else { (b + 1) * (b + 2) / 2 + (s - b - 1) * (b + 1) }
+    }
+
+    /// Number of partitions of `r` into `count` parts (or any number of them) at most `cap`.
+    fn at_most( &self, r: usize, count: Option<usize>, cap: usize ) -> u128 {
+        match count {
+            None => self.table[self.offset( r ) + cap.min(r).min(self.bounds.max_part)],
+            Some(0) => if r == 0 { 1 } else { 0 },
+            // 各部分から c, c-1, ..., 1 を引くと、c 個以下の cap-c 以下の数への分割になる
+            Some(c) if self.bounds.distinct => {
+                if r < triangular( c ) || cap < c { 0 } else { gaussian( r - triangular( c ), c, cap - c ) }
+            },
+            // 各部分から 1 を引くと、c 個以下の cap-1 以下の数への分割になる
+            Some(c) => {
+                if r < c || cap == 0 { 0 } else { gaussian( r - c, c, cap - 1 ) }
+            },
+        }
+    }
+}
+
+/// Number of partitions of `s` into at most `c` parts at most `b`, which is the coefficient of
+/// `q^s` in the Gaussian binomial coefficient `[b+c, c]_q`.
+///
+/// This computes modulo `2^128`, so the result is exact if it fits in `u128`.
+fn gaussian( s: usize, c: usize, b: usize ) -> u128 {
+    if s > c.saturating_mul(b) {
+        return 0;
+    }
+
+    // 積 (1 - q^(b+i)) / (1 - q^i) を s 次まで展開する
+    let (c, b) = (c.min(b), c.max(b));
+    let mut poly = vec![ 0u128; s + 1 ];
+    poly[0] = 1;
+    for i in 1..c+1 {
+        for d in (b.saturating_add(i)..s+1).rev() {
+            poly[d] = poly[d].wrapping_sub(poly[d - b - i]);
+        }
+        for d in i..s+1 {
+            poly[d] = poly[d].wrapping_add(poly[d - i]);
+        }
+    }
+    poly[s]
+}
+
+/// Same as `IntegerPartition::from_index_with_constraint`.
+fn unrank( n: usize, constraint: Constraint, idx: u128 ) -> Option<Vec<usize>> {
+    if idx >= total( n, constraint )? {
+        return None;
+    }
+
+    let bounds = Bounds::new( n, constraint );
+    let counter = Counter::new( n, bounds )?;
+    let (mut r, mut cap, mut count) = (n, bounds.max_part, bounds.parts);
+    let mut idx = idx;
+    let mut parts = Vec::new();
+
+    while r > 0 {
+        // 最初の部分が v 以上のものが idx 個より多くなる最大の v を探す
+        let all = counter.at_most( r, count, cap );
+        let (mut lo, mut hi) = (1, cap.min(r));
+        while lo < hi {
+            let mid = lo + (hi - lo + 1) / 2;
+            if all - counter.at_most( r, count, mid - 1 ) > idx { lo = mid } else { hi = mid - 1 }
+        }
+
+        idx -= all - counter.at_most( r, count, lo );
+        parts.push(lo);
+        r -= lo;
+        cap = bounds.cap_after( lo );
+        count = count.map(|c| c - 1);
+    }
+
+    Some(parts)
+}
+
+/// Same as `IntegerPartition::to_index_with_constraint`.
+fn rank( parts: &[usize], constraint: Constraint ) -> Option<u128> {
+    let n = parts.iter().try_fold(0usize, |sum, &x| sum.checked_add(x))?;
+    total( n, constraint )?;
+
+    let bounds = Bounds::new( n, constraint );
+    let counter = Counter::new( n, bounds )?;
+    let (mut r, mut cap, mut count) = (n, bounds.max_part, bounds.parts);
+    let mut idx: u128 = 0;
+
+    for &x in parts.iter() {
+        if x == 0 || x > cap || count == Some(0) {
+            return None;
+        }
+        // 最初の部分が x より大きいものが前にある
+        idx += counter.at_most( r, count, cap ) - counter.at_most( r, count, x );
+        r -= x;
+        cap = bounds.cap_after( x );
+        count = count.map(|c| c - 1);
+    }
+
+    if count.unwrap_or(0) == 0 { Some(idx) } else { None }
+}
+
+
+/// The partitions of `IntegerPartition` as multiplicity vectors, created by
+/// `IntegerPartition::multiplicities`.
+#[derive(Debug, Clone)]
+pub struct Multiplicities {
+    inner: IntegerPartition,
+}
+
+impl Multiplicities {
+    /// The underlying iterator of partitions.
+    pub fn inner(&self) -> &IntegerPartition {
+        &self.inner
+    }
+
+    /// The underlying iterator of partitions, e.g. to `seek` it.
+    pub fn inner_mut(&mut self) -> &mut IntegerPartition {
+        &mut self.inner
+    }
+}
+
+
+mod streaming {
+    use super::IntegerPartition;
+    use crate::count;
+    use crate::streaming::{Source, SourceBack};
+
+    impl Source for IntegerPartition {
+        type Item = [usize];
+
+        fn advance(&mut self) {
+            self.step();
+        }
+
+        fn get(&self) -> Option<&[usize]> {
+            self.cursor.current()
+        }
+
+        fn jump(&mut self, k: usize) {
+            self.seek( self.next_index().saturating_add(k as u128) );
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl SourceBack for IntegerPartition {
+        fn advance_back(&mut self) {
+            self.step_back();
+        }
+    }
+}
+
+
+mod iterator {
+    use super::{IntegerPartition, Multiplicities, to_multiplicities};
+    use crate::count;
+    use alloc::vec::Vec;
+    use core::iter::{Iterator, DoubleEndedIterator};
+
+    impl Iterator for IntegerPartition {
+        type Item = Vec<usize>;
+
+        fn next(&mut self) -> Option<Vec<usize>> {
+            self.step();
+            self.cursor.front().map(|parts| parts.to_vec())
+        }
+
+        fn nth(&mut self, k: usize) -> Option<Vec<usize>> {
+            self.seek( self.next_index().saturating_add(k as u128) );
+            self.next()
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl DoubleEndedIterator for IntegerPartition {
+        fn next_back(&mut self) -> Option<Vec<usize>> {
+            self.step_back();
+            self.cursor.back().map(|parts| parts.to_vec())
+        }
+    }
+
+    impl Iterator for Multiplicities {
+        type Item = Vec<usize>;
+
+        fn next(&mut self) -> Option<Vec<usize>> {
+            self.inner.step();
+            self.inner.cursor.front().and_then(to_multiplicities)
+        }
+
+        fn nth(&mut self, k: usize) -> Option<Vec<usize>> {
+            self.inner.seek( self.inner.next_index().saturating_add(k as u128) );
+            self.next()
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            self.inner.size_hint()
+        }
+    }
+
+    impl DoubleEndedIterator for Multiplicities {
+        fn next_back(&mut self) -> Option<Vec<usize>> {
+            self.inner.step_back();
+            self.inner.cursor.back().and_then(to_multiplicities)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::integer_partition::{Constraint, to_multiplicities, from_multiplicities};
+
+    /// All partitions of `n` into parts at most `cap`, in reverse lexicographic order.
+    fn all( n: usize, cap: usize ) -> Vec<Vec<usize>> {
+        if n == 0 {
+            return vec![ vec![] ];
+        }
+        let mut result = Vec::new();
+        for p in (1..cap.min(n)+1).rev() {
+            for mut rest in all( n - p, p ) {
+                rest.insert( 0, p );
+                result.push(rest);
+            }
+        }
+        result
+    }
+
+    fn satisfies( parts: &[usize], constraint: Constraint ) -> bool {
+        match constraint {
+            Constraint::All => true,
+            Constraint::Parts(k) => parts.len() == k,
+            Constraint::MaxPart(m) => parts.iter().all(|&x| x <= m),
+            Constraint::Distinct => parts.windows(2).all(|w| w[0] > w[1]),
+        }
+    }
+
+    fn constraints() -> Vec<Constraint> {
+        let mut constraints = vec![ Constraint::All, Constraint::Distinct ];
+        for k in 0..10 {
+            constraints.push(Constraint::Parts(k));
+            constraints.push(Constraint::MaxPart(k));
+        }
+        constraints
+    }
+
+    #[test]
+    fn detail() {
+        for n in 0..10 {
+            let all = all( n, n );
+            for constraint in constraints() {
+                let expected: Vec<Vec<usize>> = all.iter().filter(|parts| satisfies( parts, constraint )).cloned().collect();
+                let iter = IntegerPartition::with_constraint( n, constraint );
+                assert_eq!( iter.size_hint(), (expected.len(), Some(expected.len())) );
+                assert_eq!( iter.collect::<Vec<_>>(), expected, "{} {:?}", n, constraint );
+            }
+        }
+
+        let iter = IntegerPartition::new( 500 );
+        assert_eq!( iter.remaining(), count::partition_u128( 500 ) );
+        assert_eq!( iter.size_hint(), (usize::MAX, None) );
+        assert_eq!( iter.multiplicities().size_hint(), (usize::MAX, None) );
+
+        assert_eq!( IntegerPartition::new( 0 ).collect::<Vec<_>>(), vec![ vec![] ] );
+        assert_eq!( IntegerPartition::with_constraint( 0, Constraint::Parts(1) ).next(), None );
+        assert_eq!( IntegerPartition::with_constraint( 3, Constraint::MaxPart(0) ).next(), None );
+        assert_eq!( IntegerPartition::new( 100 ).total(), Some(190569292) );
+        assert_eq!( IntegerPartition::new( 2000 ).total(), None );
+    }
+
+
+    #[test]
+    fn from_index() {
+        for constraint in constraints() {
+            let iter = IntegerPartition::with_constraint( 12, constraint );
+            let total = iter.total().unwrap();
+            for (idx, parts) in iter.enumerate() {
+                assert_eq!( IntegerPartition::from_index_with_constraint( 12, constraint, idx as u128 ).unwrap(), parts );
+                assert_eq!( IntegerPartition::to_index_with_constraint( &parts, constraint ), Some(idx as u128) );
+            }
+            assert_eq!( IntegerPartition::from_index_with_constraint( 12, constraint, total ), None );
+        }
+
+        assert_eq!( IntegerPartition::from_index( 5, 3 ), Some(vec![3,1,1]) );
+        assert_eq!( IntegerPartition::to_index( &[3,1,1] ), Some(3) );
+        assert_eq!( IntegerPartition::from_index( 5, 7 ), None );
+        assert_eq!( IntegerPartition::to_index( &[1,3,1] ), None );
+        assert_eq!( IntegerPartition::to_index( &[3,0] ), None );
+        assert_eq!( IntegerPartition::to_index_with_constraint( &[3,3], Constraint::Distinct ), None );
+        assert_eq!( IntegerPartition::to_index_with_constraint( &[3,3], Constraint::Parts(3) ), None );
+        assert_eq!( IntegerPartition::to_index_with_constraint( &[3,3], Constraint::MaxPart(2) ), None );
+
+        // 部分の数が少なければ n が大きくても求まる
+        let constraint = Constraint::Parts(3);
+        let total = IntegerPartition::with_constraint( 100_000, constraint ).total().unwrap();
+        let parts = IntegerPartition::from_index_with_constraint( 100_000, constraint, total - 1 ).unwrap();
+        assert_eq!( parts, vec![33334,33333,33333] );
+        assert_eq!( IntegerPartition::to_index_with_constraint( &parts, constraint ), Some(total - 1) );
+        let parts = IntegerPartition::from_index_with_constraint( 1400, Constraint::All, 12345678901234567890 ).unwrap();
+        assert_eq!( IntegerPartition::to_index_with_constraint( &parts, Constraint::All ), Some(12345678901234567890) );
+    }
+
+
+    #[test]
+    fn next_back() {
+        for constraint in constraints() {
+            let iter = IntegerPartition::with_constraint( 9, constraint );
+            let forward: Vec<Vec<usize>> = iter.clone().collect();
+            let mut backward: Vec<Vec<usize>> = iter.clone().rev().collect();
+            backward.reverse();
+            assert_eq!( forward, backward );
+
+            // 両端から交互に取り出す
+            let mut iter = iter;
+            let (mut front, mut back) = (Vec::new(), Vec::new());
+            while let Some(x) = iter.next() {
+                front.push(x);
+                if let Some(y) = iter.next_back() {
+                    back.push(y);
+                }
+            }
+            back.reverse();
+            front.extend(back);
+            assert_eq!( front, forward );
+        }
+    }
+
+
+    #[test]
+    fn seek() {
+        let constraint = Constraint::Parts(4);
+        let all: Vec<Vec<usize>> = IntegerPartition::with_constraint( 15, constraint ).collect();
+        let mut iter = IntegerPartition::with_constraint( 15, constraint );
+        iter.seek( 20 );
+        assert_eq!( iter.next_index(), 20 );
+        assert_eq!( iter.next().unwrap(), all[20] );
+        assert_eq!( iter.nth( 3 ).unwrap(), all[24] );
+
+        let mut stream = IntegerPartition::with_constraint( 15, constraint ).streaming();
+        assert_eq!( stream.nth( 3 ).unwrap(), &all[3][..] );
+        assert_eq!( stream.next_back().unwrap(), &all[all.len() - 1][..] );
+
+        let mut count = 0;
+        let _ = IntegerPartition::with_constraint( 15, constraint ).for_each_slice(|parts| {
+            assert_eq!( parts, &all[count][..] );
+            count += 1;
+            core::ops::ControlFlow::<()>::Continue(())
+        });
+        assert_eq!( count, all.len() );
+    }
+
+
+    #[test]
+    fn multiplicities() {
+        let mut iter = IntegerPartition::new( 4 ).multiplicities();
+        assert_eq!( iter.size_hint(), (5, Some(5)) );
+        assert_eq!( iter.next().unwrap(), vec![0,0,0,1] );
+        assert_eq!( iter.next_back().unwrap(), vec![4,0,0,0] );
+        assert_eq!( iter.nth( 1 ).unwrap(), vec![0,2,0,0] );
+
+        for parts in IntegerPartition::new( 8 ) {
+            let mult = to_multiplicities( &parts ).unwrap();
+            assert_eq!( mult.len(), 8 );
+            assert_eq!( mult.iter().sum::<usize>(), parts.len() );
+            assert_eq!( from_multiplicities( &mult ), parts );
+        }
+
+        assert_eq!( to_multiplicities( &[] ), Some(vec![]) );
+        assert_eq!( to_multiplicities( &[2,0] ), None );
+        assert_eq!( from_multiplicities( &[1,0,2] ), vec![3,3,1] );
+    }
+}
@@ -0,0 +1,223 @@
+//! Enumerating arbitrary items instead of `0..n`.
+//!
+//! ```
+//! use enumcombinatorics::*;
+//!
+//! let items = ["a", "b", "c"];
+//! let mut iter = items.combinations( 2 );
+//!
+//! assert_eq!( iter.next().unwrap(), &[&"a", &"b"] );
+//! assert_eq!( iter.next().unwrap(), &[&"a", &"c"] );
+//! assert_eq!( iter.next().unwrap(), &[&"b", &"c"] );
+//! assert_eq!( iter.next(), None );
+//! ```
+
+
+use crate::{Permutation, Sequence, Combination, Multichoose, Streaming};
+use alloc::vec::Vec;
+
+
+/// Maps the indices generated by `I` to references into `items`.
+///
+/// Created by `Combination::of` etc. or the `SliceExt` trait. It yields `Vec<&T>`,
+/// and its `streaming()` yields `[&T]`.
+#[derive(Debug, Clone)]
+pub struct Items<'a, T, I> {
+    items: &'a [T],
+    indices: I,
+    /// The current item for `Streaming`.
+    buf: Vec<&'a T>,
+}
+
+impl<'a, T, I> Items<'a, T, I> {
+    /// `indices` must generate numbers less than `items.len()`.
+    pub fn new( items: &'a [T], indices: I ) -> Self {
+        Items {
+            items,
+            indices,
+            buf: Vec::new(),
+        }
+    }
+
+    /// The items to choose from.
+    pub fn items(&self) -> &'a [T] {
+        self.items
+    }
+
+    /// The underlying iterator of indices.
+    pub fn indices(&self) -> &I {
+        &self.indices
+    }
+
+    /// The underlying iterator of indices, e.g. to `seek` it.
+    pub fn indices_mut(&mut self) -> &mut I {
+        &mut self.indices
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
+}
+
+
+/// Enumerates permutations etc. of the elements of a slice.
+pub trait SliceExt<T> {
+    /// Same as `Permutation::of`.
+    fn permutations( &self, dim: usize ) -> Items<'_, T, Permutation>;
+
+    /// Same as `Sequence::of`.
+    fn sequences( &self, dim: usize ) -> Items<'_, T, Sequence>;
+
+    /// Same as `Combination::of`.
+    fn combinations( &self, dim: usize ) -> Items<'_, T, Combination>;
+
+    /// Same as `Multichoose::of`.
+    fn multichooses( &self, dim: usize ) -> Items<'_, T, Multichoose>;
+}
+
+impl<T> SliceExt<T> for [T] {
+    fn permutations( &self, dim: usize ) -> Items<'_, T, Permutation> {
+        Permutation::of( self, dim )
+    }
+
+    fn sequences( &self, dim: usize ) -> Items<'_, T, Sequence> {
+        Sequence::of( self, dim )
+    }
+
+    fn combinations( &self, dim: usize ) -> Items<'_, T, Combination> {
+        Combination::of( self, dim )
+    }
+
+    fn multichooses( &self, dim: usize ) -> Items<'_, T, Multichoose> {
+        Multichoose::of( self, dim )
+    }
+}
+
+
+mod streaming {
+    use super::Items;
+    use crate::streaming::{Source, SourceBack};
+
+    impl<'a, T, I> Items<'a, T, I>
+        where I: Source<Item = [usize]>
+    {
+        fn fill(&mut self) {
+            if let Some(indices) = self.indices.get() {
+                let items = self.items;
+                self.buf.clear();
+                self.buf.extend( indices.iter().map(|&i| &items[i]) );
+            }
+        }
+    }
+
+    impl<'a, T, I> Source for Items<'a, T, I>
+        where I: Source<Item = [usize]>
+    {
+        type Item = [&'a T];
+
+        fn advance(&mut self) {
+            self.indices.advance();
+            self.fill();
+        }
+
+        fn get(&self) -> Option<&[&'a T]> {
+            self.indices.get().map(|_| &self.buf[..])
+        }
+
+        fn jump(&mut self, k: usize) {
+            self.indices.jump(k);
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            self.indices.size_hint()
+        }
+    }
+
+    impl<'a, T, I> SourceBack for Items<'a, T, I>
+        where I: SourceBack<Item = [usize]>
+    {
+        fn advance_back(&mut self) {
+            self.indices.advance_back();
+            self.fill();
+        }
+    }
+}
+
+
+mod iterator {
+    use super::Items;
+    use alloc::vec::Vec;
+    use core::iter::{Iterator, DoubleEndedIterator};
+
+    impl<'a, T, I> Items<'a, T, I> {
+        fn lookup( &self, indices: Vec<usize> ) -> Vec<&'a T> {
+            let items = self.items;
+            indices.into_iter().map(|i| &items[i]).collect()
+        }
+    }
+
+    impl<'a, T, I> Iterator for Items<'a, T, I>
+        where I: Iterator<Item = Vec<usize>>
+    {
+        type Item = Vec<&'a T>;
+
+        fn next(&mut self) -> Option<Vec<&'a T>> {
+            self.indices.next().map(|indices| self.lookup(indices))
+        }
+
+        fn nth(&mut self, k: usize) -> Option<Vec<&'a T>> {
+            self.indices.nth(k).map(|indices| self.lookup(indices))
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            self.indices.size_hint()
+        }
+    }
+
+    impl<'a, T, I> DoubleEndedIterator for Items<'a, T, I>
+        where I: DoubleEndedIterator<Item = Vec<usize>>
+    {
+        fn next_back(&mut self) -> Option<Vec<&'a T>> {
+            self.indices.next_back().map(|indices| self.lookup(indices))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn same_as_indices() {
+        let items = ['a', 'b', 'c', 'd'];
+        let mut iter = items.permutations( 2 );
+        let mut indices = Permutation::new( 2, 4 );
+        for perm in iter.by_ref() {
+            let expected: Vec<&char> = indices.next().unwrap().iter().map(|&i| &items[i]).collect();
+            assert_eq!( perm, &expected[..] );
+        }
+        assert_eq!( indices.next(), None );
+    }
+
+
+    #[test]
+    fn constructors() {
+        let items = vec![10, 20, 30];
+        assert_eq!( Sequence::of( &items, 2 ).size_hint(), (9, Some(9)) );
+        assert_eq!( items.multichooses( 2 ).size_hint(), (6, Some(6)) );
+
+        let mut iter = Combination::of( &items, 2 );
+        assert_eq!( iter.next_back().unwrap(), &[&20, &30] );
+        assert_eq!( iter.next().unwrap(), &[&10, &20] );
+        assert_eq!( iter.indices().remaining(), Some(1) );
+        assert_eq!( iter.next().unwrap(), &[&10, &30] );
+        assert_eq!( iter.next(), None );
+
+        let mut iter = Multichoose::of( &items, 2 );
+        iter.indices_mut().seek( 1 );
+        assert_eq!( iter.next().unwrap(), &[&10, &20] );
+        assert_eq!( iter.nth( 1 ).unwrap(), &[&20, &20] );
+    }
+}
@@ -1,10 +1,11 @@
 //! Collection of permutation, combination, sequence and multichoose of `usize`s.
 //! 
-//! This crate provides iterators (or streaming iterators) generating all possible
+//! This crate provides iterators (and streaming iterators) generating all possible
 //! permutations, combinations, ... of 0, 1, ..., n-1. Here is an example:
 //! 
 //! ```
 //! use enumcombinatorics::*;
+//! # #[cfg(feature = "alloc")] {
 //! 
 //! let dim = 2;
 //! let n = 3;
@@ -21,6 +22,7 @@
 //! assert_eq!( iter.next().unwrap(), &[2,1] );
 //! assert_eq!( iter.next().unwrap(), &[2,2] );
 //! assert_eq!( iter.next(), None );
+//! # }
 //! ```
 //! 
 //! 
@@ -30,6 +32,12 @@
 //! - `Sequence`: 1 number appears any times in meaningful order
 //! - `Combination`: 1 number appears once in meaningless order
 //! - `Multichoose`: 1 number appears any times in meaningless order
+//! - `SetPartition`: numbers are divided into blocks (see `set_partition`)
+//! - `IntegerPartition`: a number is divided into positive parts (see `integer_partition`)
+//! - `Composition`: a number is divided into parts in meaningful order (see `composition`)
+//! 
+//! None of them implements `ExactSizeIterator`, since the number of items easily exceeds `usize`.
+//! `remaining()` tells the exact number of items left as `Option<u128>` instead.
 //! 
 //! 
 //! 
@@ -39,13 +47,22 @@
 //! 
 //! # Crate feature flags
 //! 
+//! The crate is `no_std`.
+//! 
+//! - alloc (default)
+//!   - Enables the `Vec`-based iterators, `Items` and `Streaming`.
+//!   - Without it, `array`, `count`, `Order` and `Error` are still available.
+//! - std
+//!   - Implements `std::error::Error` for `Error`.
 //! - streaming
-//! -- Instead of `std::iter::Iterator`, use `streaming_iterator::StreamingIterator`
-//!    via the `streaming_iterator` crate.
-//! -- This feature reduces the overhead for the `next()` method, because `Iterator` 
-//!    trait requires cloning `Vec<usize>` every time.
-//! -- If this flag is enabled, then you can not use `for` loop due to the lack of
-//!    `std::iter::Iterator`.
+//!   - Implements `streaming_iterator::StreamingIterator` for `Streaming`, the borrowed view
+//!     returned by `streaming()` of each iterator.
+//!   - `Iterator` is implemented regardless of this flag, so `for` loops keep working.
+//! - bigint
+//!   - Enables `count::big`, which counts with `num_bigint::BigUint`.
+//! - rand
+//!   - Enables `sample( .., rng )` of `Permutation`, `Sequence`, `Combination` and `Multichoose`,
+//!     which returns a uniformly random item in the same form as the iterators generate.
 //! 
 //! 
 //! 
@@ -55,22 +72,75 @@
 //! 
 
 
+#![cfg_attr(not(test), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+
 #[cfg(feature = "streaming")]
-pub use streaming_iterator::StreamingIterator;
-#[cfg(not(feature = "streaming"))]
-pub use std::iter::Iterator;
+pub use streaming_iterator::{StreamingIterator, DoubleEndedStreamingIterator};
+pub use core::iter::{Iterator, DoubleEndedIterator};
+
+
+pub mod count;
+mod successor;
+#[cfg(feature = "alloc")]
+mod cursor;
 
+mod error;
+pub use crate::error::Error;
 
+mod order;
+pub use crate::order::Order;
+
+#[cfg(feature = "alloc")]
 pub mod permutation;
+#[cfg(feature = "alloc")]
 pub use crate::permutation::Permutation;
 
+#[cfg(feature = "alloc")]
 pub mod sequence;
+#[cfg(feature = "alloc")]
 pub use crate::sequence::Sequence;
 
+#[cfg(feature = "alloc")]
 pub mod combination;
+#[cfg(feature = "alloc")]
 pub use combination::Combination;
 
+#[cfg(feature = "alloc")]
 pub mod multichoose;
+#[cfg(feature = "alloc")]
 pub use crate::multichoose::Multichoose;
 
+#[cfg(feature = "alloc")]
+pub mod set_partition;
+#[cfg(feature = "alloc")]
+pub use crate::set_partition::SetPartition;
+
+#[cfg(feature = "alloc")]
+pub mod integer_partition;
+#[cfg(feature = "alloc")]
+pub use crate::integer_partition::IntegerPartition;
+
+#[cfg(feature = "alloc")]
+pub mod composition;
+#[cfg(feature = "alloc")]
+pub use crate::composition::Composition;
+
+pub mod array;
+
+#[cfg(feature = "alloc")]
+pub mod items;
+#[cfg(feature = "alloc")]
+pub use crate::items::{Items, SliceExt};
+
+#[cfg(feature = "alloc")]
+pub mod streaming;
+#[cfg(feature = "alloc")]
+pub use crate::streaming::Streaming;
+
 
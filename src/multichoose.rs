@@ -1,132 +1,306 @@
+use crate::{count, combination, Error, Items, Order, Streaming};
+use crate::cursor::Cursor;
+use core::convert::TryFrom;
+use core::ops::ControlFlow;
+use crate::successor::multichoose::{next_state, prev_state};
+use alloc::vec;
+use alloc::vec::Vec;
+
 
 #[derive(Debug, Clone)]
 pub struct Multichoose {
-    state: Vec<usize>,
-    status: Status,
+    cursor: Cursor,
     dim: usize,
     n: usize,
-    end: Vec<usize>,
-}
-
-#[derive(Debug, Clone)]
-enum Status {
-    Ini,
-    Run,
-    End,
+    order: Order,
 }
 
 impl Multichoose {
+    /// Generates all non-decreasing sequences of `dim` numbers out of `0..n`.
+    ///
+    /// There is exactly one (empty) multiset if `dim == 0`, and none if `n == 0` and `dim > 0`.
     pub fn new( dim: usize, n: usize ) -> Self {
+        Multichoose::with_order( dim, n, Order::Lex )
+    }
+
+    /// Same as `Multichoose::new`, but generates the multisets in `order`.
+    pub fn with_order( dim: usize, n: usize, order: Order ) -> Self {
+        let first = if order.is_rev() { last( dim, n ) } else { vec![ 0; dim ] };
         Multichoose {
-            state: vec![ 0; dim ],
-            status: Status::Ini,
+            cursor: Cursor::new( first, dim, count::multichoose_u128( n, dim ) ),
             dim,
             n,
-            end: vec![ n-1; dim ],
+            order,
         }
     }
+
+    /// Same as `Multichoose::new`, but returns an error instead of an iterator generating
+    /// no multisets if `n == 0` and `dim > 0`.
+    pub fn try_new( dim: usize, n: usize ) -> Result<Self, Error> {
+        if n == 0 && dim > 0 {
+            Err( Error::EmptyRange { dim } )
+        } else {
+            Ok( Multichoose::new( dim, n ) )
+        }
+    }
+
+    /// Generates all choices of `dim` elements of `items` with repetition, in the order of `items`, as references.
+    ///
+    /// Same as `Multichoose::new( dim, items.len() )` with each index `i` replaced by `&items[i]`.
+    pub fn of<T>( items: &[T], dim: usize ) -> Items<'_, T, Self> {
+        Items::new( items, Multichoose::new( dim, items.len() ) )
+    }
+
+    /// Order of the multisets.
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
+    /// Number of all multisets, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::multichoose_u128( self.n, self.dim )
+    }
+
+    /// Number of multisets not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.cursor.remaining()
+    }
+
+    /// Returns the `idx`-th multiset (counting from 0) in the order of the iterator,
+    /// or `None` if there are not so many multisets.
+    ///
+    /// A non-decreasing `[m_0, m_1, ...]` corresponds to the combination `[m_0, m_1+1, ...]`
+    /// of `n+dim-1` numbers in the same order, so there are `binomial(n+dim-1, dim)` multisets.
+    pub fn from_index( dim: usize, n: usize, idx: usize ) -> Option<Vec<usize>> {
+        unrank( dim, n, Order::Lex, idx as u128 )
+    }
+
+    /// Returns the position of `multiset` in the order of the iterator, that is, the inverse of
+    /// `Multichoose::from_index`.
+    ///
+    /// Returns `None` if `multiset` is not a non-decreasing sequence of numbers less than `n`
+    /// or the index does not fit in `usize`.
+    pub fn to_index( multiset: &[usize], n: usize ) -> Option<usize> {
+        usize::try_from( rank( multiset, n, Order::Lex )? ).ok()
+    }
+
+    /// Same as `Multichoose::from_index`, for `Multichoose::with_order( dim, n, order )`.
+    pub fn from_index_with_order( dim: usize, n: usize, order: Order, idx: u128 ) -> Option<Vec<usize>> {
+        unrank( dim, n, order, idx )
+    }
+
+    /// Same as `Multichoose::to_index`, for `Multichoose::with_order( multiset.len(), n, order )`.
+    ///
+    /// For the reverse orders, this also returns `None` if the number of multisets
+    /// does not fit in `u128`.
+    pub fn to_index_with_order( multiset: &[usize], n: usize, order: Order ) -> Option<u128> {
+        rank( multiset, n, order )
+    }
+
+    /// Moves the iterator so that the next call of `next()` returns the `idx`-th multiset,
+    /// without generating the ones in between.
+    pub fn seek( &mut self, idx: u128 ) {
+        let (dim, n, order) = (self.dim, self.n, self.order);
+        self.cursor.seek( idx, || unrank( dim, n, order, idx ) );
+    }
+
+    /// Position of the multiset which the next call of `next()` returns.
+    pub fn next_index(&self) -> u128 {
+        self.cursor.next_index()
+    }
+
+    /// Calls `f` on each remaining multiset by reference without allocating, until `f` returns
+    /// `ControlFlow::Break`. The iterator can be resumed after it.
+    pub fn for_each_slice<B, F>( &mut self, f: F ) -> ControlFlow<B>
+        where F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        let (n, order) = (self.n, self.order);
+        self.cursor.for_each(|state| advance( state, n, order, true ), f)
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
+
+    fn step(&mut self) {
+        let (n, order) = (self.n, self.order);
+        self.cursor.step(|state| advance( state, n, order, true ));
+    }
+
+    fn step_back(&mut self) {
+        let (dim, n, order) = (self.dim, self.n, self.order);
+        let last = || if order.is_rev() { vec![ 0; dim ] } else { last( dim, n ) };
+        self.cursor.step_back(last, |state| advance( state, n, order, false ));
+    }
 }
 
 
-#[cfg(feature = "streaming")]
-mod streaming_iterator {
-    use super::{Multichoose, Status};
-    use streaming_iterator::StreamingIterator;
+/// The last multiset in lexicographic order, which is also the last one in colexicographic order.
+fn last( dim: usize, n: usize ) -> Vec<usize> {
+    // n == 0 なら dim == 0 か空で、中身は使われない
+    vec![ n.saturating_sub(1); dim ]
+}
+
+
+/// Position of `multiset` in `order`, through the corresponding combination.
+fn rank( multiset: &[usize], n: usize, order: Order ) -> Option<u128> {
+    let dim = multiset.len();
+    let comb: Vec<usize> = multiset.iter().enumerate().map(|(k, &x)| x.checked_add(k)).collect::<Option<_>>()?;
+
+    combination::rank( &comb, n.checked_add(dim)?.saturating_sub(1), order )
+}
+
+/// Same as `Multichoose::from_index_with_order`.
+fn unrank( dim: usize, n: usize, order: Order, idx: u128 ) -> Option<Vec<usize>> {
+    let mut vec = combination::unrank_with_order( dim, n.checked_add(dim)?.saturating_sub(1), order, idx )?;
+    for (k, x) in vec.iter_mut().enumerate() {
+        *x -= k;
+    }
 
-    impl StreamingIterator for Multichoose {
+    Some(vec)
+}
+
+/// Rearranges `state` into the next multiset in `order` if `forward`, or the previous one
+/// otherwise. Returns `false` if there is no such multiset.
+fn advance( state: &mut [usize], n: usize, order: Order, forward: bool ) -> bool {
+    match (order.is_colex(), forward != order.is_rev()) {
+        (false, true) => next_state( state, n ),
+        (false, false) => prev_state( state, n ),
+        (true, true) => next_colex( state, n ),
+        (true, false) => prev_colex( state ),
+    }
+}
+
+
+/// Rearranges `state` into the next multiset in colexicographic order,
+/// or returns `false` if it is the last one.
+fn next_colex( state: &mut [usize], n: usize ) -> bool {
+    let dim = state.len();
+
+    // 1 を足せる一番左の位置を探し、それより左は 0 にする
+    match (0..dim).find(|&i| state[i] + 1 < if i + 1 == dim { n } else { state[i+1] + 1 }) {
+        Some(i) => {
+            state[i] += 1;
+            for x in state[..i].iter_mut() {
+                *x = 0;
+            }
+            true
+        },
+        None => false,
+    }
+}
+
+/// Rearranges `state` into the previous multiset in colexicographic order,
+/// or returns `false` if it is the first one.
+fn prev_colex( state: &mut [usize] ) -> bool {
+    // 1 を引ける一番左の位置を探し、それより左はその数でそろえる
+    match state.iter().position(|&x| x > 0) {
+        Some(i) => {
+            state[i] -= 1;
+            let x = state[i];
+            for y in state[..i].iter_mut() {
+                *y = x;
+            }
+            true
+        },
+        None => false,
+    }
+}
+
+
+mod streaming {
+    use super::Multichoose;
+    use crate::count;
+    use crate::streaming::{Source, SourceBack};
+
+    impl Source for Multichoose {
         type Item = [usize];
 
         fn advance(&mut self) {
-            match self.status {
-                Status::Ini => { self.status = Status::Run; },
-                Status::Run => {
-                    if self.state == self.end {
-                        self.status = Status::End;
-                    } else {
-                        // 1 を足す
-                        self.state[self.dim-1] += 1;
-                        
-                        while {
-                            // 繰り上げ処理
-                            for idx in (1..self.dim).rev() {
-                                if self.state[idx] == self.n {
-                                    self.state[idx-1] += 1;
-                                }
-                            }
-                            for idx in 1..self.dim {
-                                if self.state[idx] == self.n {
-                                    self.state[idx] = self.state[idx-1];
-                                }
-                            }
-
-                            self.state[self.dim-1] >= self.n
-                        } {}
-                    }
-                },
-                Status::End => {},
-            }
+            self.step();
         }
 
         fn get(&self) -> Option<&[usize]> {
-            match self.status {
-                Status::Run => Some(&self.state),
-                _ => None,
-            }
+            self.cursor.current()
+        }
+
+        fn jump(&mut self, k: usize) {
+            self.seek( self.next_index().saturating_add(k as u128) );
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
         }
     }
-}
 
+    impl SourceBack for Multichoose {
+        fn advance_back(&mut self) {
+            self.step_back();
+        }
+    }
+}
 
 
-#[cfg(not(feature = "streaming"))]
 mod iterator {
-    use super::{Multichoose, Status};
-    use std::iter::Iterator;
+    use super::Multichoose;
+    use crate::count;
+    use alloc::vec::Vec;
+    use core::iter::{Iterator, DoubleEndedIterator};
 
     impl Iterator for Multichoose {
         type Item = Vec<usize>;
 
         fn next(&mut self) -> Option<Vec<usize>> {
-            match self.status {
-                Status::Ini => { self.status = Status::Run; },
-                Status::Run => {
-                    if self.state == self.end {
-                        self.status = Status::End;
-                    } else {
-                        // 1 を足す
-                        self.state[self.dim-1] += 1;
-                        
-                        while {
-                            // 繰り上げ処理
-                            for idx in (1..self.dim).rev() {
-                                if self.state[idx] == self.n {
-                                    self.state[idx-1] += 1;
-                                }
-                            }
-                            for idx in 1..self.dim {
-                                if self.state[idx] == self.n {
-                                    self.state[idx] = self.state[idx-1];
-                                }
-                            }
-
-                            self.state[self.dim-1] >= self.n
-                        } {}
-                    }
-                },
-                Status::End => {},
-            }
+            self.step();
+            self.cursor.front().map(|multiset| multiset.to_vec())
+        }
 
-            match self.status {
-                Status::Run => Some(self.state.clone()),
-                _ => None,
-            }
+        fn nth(&mut self, k: usize) -> Option<Vec<usize>> {
+            self.seek( self.next_index().saturating_add(k as u128) );
+            self.next()
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl DoubleEndedIterator for Multichoose {
+        fn next_back(&mut self) -> Option<Vec<usize>> {
+            self.step_back();
+            self.cursor.back().map(|multiset| multiset.to_vec())
         }
     }
 }
 
 
+#[cfg(feature = "rand")]
+mod sample {
+    use super::Multichoose;
+    use crate::Combination;
+    use alloc::vec::Vec;
+    use rand::Rng;
 
+    impl Multichoose {
+        /// Returns a multiset of `dim` numbers out of `0..n` chosen uniformly at random,
+        /// or `None` if `n == 0` and `dim > 0` or if `n + dim - 1` overflows `usize`.
+        /// It is non-decreasing, as the iterator generates.
+        ///
+        /// This samples a combination out of `0..n+dim-1` and converts it by stars and bars.
+        pub fn sample<R: Rng + ?Sized>( dim: usize, n: usize, rng: &mut R ) -> Option<Vec<usize>> {
+            if dim == 0 {
+                return Some(Vec::new());
+            }
 
+            let mut comb = Combination::sample( dim, n.checked_add(dim - 1)?, rng )?;
+            for (k, x) in comb.iter_mut().enumerate() {
+                *x -= k;
+            }
+            Some(comb)
+        }
+    }
+}
 
 
 #[cfg(test)]
@@ -162,4 +336,244 @@ mod tests {
         assert_eq!( iter.next().unwrap(), &[3,3,3] );
         assert_eq!( iter.next(), None );
     }
+
+
+    #[test]
+    fn from_index() {
+        let dim = 3;
+        let n = 5;
+
+        let mut iter = Multichoose::new( dim, n );
+        let mut idx = 0;
+
+        for elem in iter.by_ref() {
+            let vec = Multichoose::from_index( dim, n, idx ).unwrap();
+            let vec: &[usize] = &vec;
+            assert_eq!( elem, vec );
+            assert_eq!( Multichoose::to_index( vec, n ), Some(idx) );
+            idx += 1;
+        }
+
+        assert_eq!( Multichoose::from_index( dim, n, idx ), None );
+        assert_eq!( Multichoose::to_index( &[0,2,1], n ), None );
+        assert_eq!( Multichoose::to_index( &[0,1,5], n ), None );
+        assert_eq!( Multichoose::to_index( &[usize::MAX, usize::MAX], usize::MAX ), None );
+        assert_eq!( Multichoose::from_index( 2, usize::MAX, 0 ), None );
+    }
+
+
+    #[test]
+    fn size_hint() {
+        let mut iter = Multichoose::new( 3, 4 );
+        assert_eq!( iter.total(), Some(20) );
+
+        for rest in (0..20).rev() {
+            assert_eq!( iter.size_hint(), (rest+1, Some(rest+1)) );
+            iter.next().unwrap();
+            assert_eq!( iter.remaining(), Some(rest as u128) );
+        }
+
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.size_hint(), (0, Some(0)) );
+
+        let m = usize::MAX as u128;
+        let mut iter = Multichoose::new( 2, usize::MAX );
+        assert_eq!( iter.total(), Some(m * (m + 1) / 2) );
+        assert_eq!( iter.next().unwrap(), &[0,0] );
+        assert_eq!( iter.next_back().unwrap(), &[usize::MAX - 1, usize::MAX - 1] );
+    }
+
+
+    #[test]
+    fn next_back() {
+        let dim = 3;
+        let n = 4;
+
+        let mut forward = Vec::new();
+        let mut iter = Multichoose::new( dim, n );
+        for elem in iter.by_ref() {
+            forward.push( elem.to_vec() );
+        }
+
+        let mut backward = Vec::new();
+        let mut iter = Multichoose::new( dim, n );
+        while let Some(elem) = iter.next_back() {
+            backward.push( elem.to_vec() );
+        }
+        backward.reverse();
+        assert_eq!( backward, forward );
+
+        // 両端から交互に取り出す
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        let mut iter = Multichoose::new( dim, n );
+        while let Some(elem) = iter.next() {
+            front.push( elem.to_vec() );
+            match iter.next_back() {
+                Some(elem) => back.push( elem.to_vec() ),
+                None => break,
+            }
+        }
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.next_back(), None );
+        back.reverse();
+        front.append( &mut back );
+        assert_eq!( front, forward );
+    }
+
+
+    #[test]
+    fn seek() {
+        let dim = 3;
+        let n = 4;
+
+        let mut all = Vec::new();
+        let mut iter = Multichoose::new( dim, n );
+        for elem in iter.by_ref() {
+            all.push( elem.to_vec() );
+        }
+
+        for (idx, elem) in all.iter().enumerate() {
+            let mut iter = Multichoose::new( dim, n );
+            iter.seek( idx as u128 );
+            assert_eq!( iter.next().unwrap(), &elem[..] );
+            assert_eq!( iter.next_index(), idx as u128 + 1 );
+        }
+
+        let mut iter = Multichoose::new( dim, n );
+        assert_eq!( iter.nth(3).unwrap(), &all[3][..] );
+        assert_eq!( iter.nth(2).unwrap(), &all[6][..] );
+        assert_eq!( iter.nth(all.len()), None );
+        iter.seek( 1 );
+        assert_eq!( iter.next().unwrap(), &all[1][..] );
+
+        // 後ろから取り出したものには飛ばない
+        let mut iter = Multichoose::new( dim, n );
+        iter.next_back();
+        iter.seek( all.len() as u128 - 1 );
+        assert_eq!( iter.next(), None );
+
+        // 添字が usize に収まらない場合
+        let mut iter = Multichoose::new( 40, 60 );
+        let idx = iter.total().unwrap() / 3;
+        iter.seek( idx );
+        let elem = iter.next().unwrap().to_vec();
+        let next = iter.next().unwrap().to_vec();
+        iter.seek( idx + 1 );
+        assert_eq!( iter.next().unwrap(), &next[..] );
+        assert_ne!( elem, next );
+    }
+
+
+    #[test]
+    fn edge_cases() {
+        // 空の列がちょうど 1 つ
+        for n in 0..3 {
+            let mut iter = Multichoose::new( 0, n );
+            assert_eq!( iter.total(), Some(1) );
+            assert_eq!( iter.next().unwrap(), &[] );
+            assert_eq!( iter.next(), None );
+            assert_eq!( iter.next_back(), None );
+
+            let mut iter = Multichoose::new( 0, n );
+            assert_eq!( iter.next_back().unwrap(), &[] );
+            assert_eq!( iter.next(), None );
+            assert!( Multichoose::try_new( 0, n ).is_ok() );
+        }
+
+        let mut iter = Multichoose::new( 4, 0 );
+        assert_eq!( iter.total(), Some(0) );
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.next_back(), None );
+        assert_eq!( Multichoose::try_new( 4, 0 ).unwrap_err(), Error::EmptyRange { dim: 4 } );
+    }
+
+
+    #[test]
+    fn order() {
+        let (dim, n) = (3, 4);
+        let mut lex = Vec::new();
+        let mut iter = Multichoose::new( dim, n );
+        for x in iter.by_ref() {
+            lex.push(x.to_vec());
+        }
+
+        for &order in &[Order::Lex, Order::Colex, Order::RevLex, Order::RevColex] {
+            let mut expected = lex.clone();
+            if order == Order::Colex || order == Order::RevColex {
+                expected.sort_by_key(|x| x.iter().rev().cloned().collect::<Vec<usize>>());
+            }
+            if order == Order::RevLex || order == Order::RevColex {
+                expected.reverse();
+            }
+
+            let mut iter = Multichoose::with_order( dim, n, order );
+            assert_eq!( iter.order(), order );
+            let mut idx = 0;
+            for x in iter.by_ref() {
+                let x = x.to_vec();
+                assert_eq!( x, expected[idx] );
+                assert_eq!( Multichoose::from_index_with_order( dim, n, order, idx as u128 ), Some(x.clone()) );
+                assert_eq!( Multichoose::to_index_with_order( &x, n, order ), Some(idx as u128) );
+                idx += 1;
+            }
+            assert_eq!( idx, expected.len() );
+            assert_eq!( Multichoose::from_index_with_order( dim, n, order, idx as u128 ), None );
+
+            let mut iter = Multichoose::with_order( dim, n, order );
+            let mut idx = expected.len();
+            while let Some(x) = iter.next_back() {
+                idx -= 1;
+                assert_eq!( x.to_vec(), expected[idx] );
+            }
+            assert_eq!( idx, 0 );
+
+            let mut iter = Multichoose::with_order( dim, n, order );
+            iter.seek( 7 );
+            assert_eq!( iter.next().unwrap().to_vec(), expected[7] );
+        }
+
+        // 0..m からなるものが先に来る
+        let mut iter = Multichoose::with_order( 2, n, Order::Colex );
+        assert_eq!( iter.next().unwrap(), &[0,0] );
+        assert_eq!( iter.nth( 1 ).unwrap(), &[1,1] );
+    }
+
+
+    #[test]
+    fn for_each_slice() {
+        use std::ops::ControlFlow;
+
+        let mut iter = Multichoose::new( 2, 3 );
+        let mut all = Vec::new();
+        let flow = iter.for_each_slice(|multiset| {
+            all.push(multiset.to_vec());
+            if all.len() == 4 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        });
+        assert_eq!( flow, ControlFlow::Break(()) );
+        assert_eq!( all, vec![ vec![0,0], vec![0,1], vec![0,2], vec![1,1] ] );
+        assert_eq!( iter.next().unwrap(), &[1,2] );
+        assert_eq!( iter.for_each_slice(|_| ControlFlow::Break(())), ControlFlow::Break(()) );
+        assert_eq!( iter.next(), None );
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn sample() {
+        use rand::{SeedableRng, rngs::SmallRng};
+
+        let mut rng = SmallRng::seed_from_u64( 1 );
+        let mut hist = [0; 20];
+        for _ in 0..20000 {
+            let multiset = Multichoose::sample( 3, 4, &mut rng ).unwrap();
+            hist[ Multichoose::to_index( &multiset, 4 ).unwrap() ] += 1;
+        }
+        assert!( hist.iter().all(|&c| 800 < c && c < 1200), "{:?}", hist );
+
+        assert_eq!( Multichoose::sample( 3, 1, &mut rng ), Some(vec![0, 0, 0]) );
+        assert_eq!( Multichoose::sample( 0, 0, &mut rng ), Some(vec![]) );
+        assert_eq!( Multichoose::sample( 1, 0, &mut rng ), None );
+        assert!( Multichoose::sample( 1, usize::MAX, &mut rng ).is_some() );
+        assert_eq!( Multichoose::sample( 2, usize::MAX, &mut rng ), None );
+    }
 }
@@ -0,0 +1,34 @@
+/// Order in which `Combination` and `Multichoose` generate their items.
+///
+/// Each item is a non-decreasing sequence. For `[0,1], [0,2], [1,2], [0,3], ...`:
+///
+/// - `Lex` compares the smallest numbers first: `[0,1], [0,2], [0,3], [1,2], ...`.
+/// - `Colex` compares the largest numbers first, so all items made of `0..m` come before
+///   any item containing `m`: `[0,1], [0,2], [1,2], [0,3], ...`.
+/// - `RevLex` and `RevColex` are the reverse of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Order {
+    /// Lexicographic order.
+    #[default]
+    Lex,
+    /// Colexicographic order.
+    Colex,
+    /// Reverse lexicographic order.
+    RevLex,
+    /// Reverse colexicographic order.
+    RevColex,
+}
+
+// 使うのは Vec 版のイテレータだけ
+#[cfg(feature = "alloc")]
+impl Order {
+    /// Whether the order compares the largest numbers first.
+    pub(crate) fn is_colex(self) -> bool {
+        matches!(self, Order::Colex | Order::RevColex)
+    }
+
+    /// Whether the order is descending.
+    pub(crate) fn is_rev(self) -> bool {
+        matches!(self, Order::RevLex | Order::RevColex)
+    }
+}
@@ -1,140 +1,880 @@
-//! 
-//! 
+use crate::{count, Error, Items, Streaming};
+use crate::cursor::Cursor;
+use crate::successor::permutation::shift;
+use core::convert::TryFrom;
+use core::ops::ControlFlow;
+use alloc::vec;
+use alloc::vec::Vec;
 
 
 #[derive(Debug, Clone)]
 pub struct Permutation {
-    state: Vec<usize>,
-    status: Status,
+    cursor: Cursor,
     dim: usize,
     n: usize,
-    end: Vec<usize>,
-}
-
-#[derive(Debug, Clone)]
-enum Status {
-    Ini,
-    Run,
-    End
 }
 
 impl Permutation {
+    /// Generates all arrangements of `dim` distinct numbers out of `0..n`.
+    ///
+    /// There is exactly one (empty) permutation if `dim == 0`, and none if `dim > n`.
     pub fn new( dim: usize, n: usize ) -> Permutation {
         Permutation {
-            state: (0..dim).collect(),
-            status: Status::Ini,
+            cursor: Cursor::new( (0..n).collect(), dim, count::falling_factorial_u128( n, dim ) ),
             dim, n,
-            end: (n-dim..n).rev().collect(),
         }
     }
 
-    fn has_duplication(&self) -> bool {
-        let mut list: Vec<bool> = vec![ false; self.n ];
-        for &i in self.state.iter() {
-            if list[i] {
-                return true;
-            } else {
-                list[i] = true;
+    /// Same as `Permutation::new`, but returns an error instead of an iterator generating
+    /// no permutations if `dim > n`.
+    pub fn try_new( dim: usize, n: usize ) -> Result<Self, Error> {
+        if dim > n {
+            Err( Error::DimTooLarge { dim, n } )
+        } else {
+            Ok( Permutation::new( dim, n ) )
+        }
+    }
+
+    /// Generates all orderings of `dim` distinct elements of `items`, as references.
+    ///
+    /// Same as `Permutation::new( dim, items.len() )` with each index `i` replaced by `&items[i]`.
+    pub fn of<T>( items: &[T], dim: usize ) -> Items<'_, T, Self> {
+        Items::new( items, Permutation::new( dim, items.len() ) )
+    }
+
+    /// Number of all permutations, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::falling_factorial_u128( self.n, self.dim )
+    }
+
+    /// Number of permutations not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.cursor.remaining()
+    }
+
+    /// Returns the `idx`-th permutation (counting from 0) in the order of the iterator,
+    /// or `None` if there are not so many permutations.
+    ///
+    /// `idx` is read in the falling factorial number system: its `k`-th digit (of base `n-k`)
+    /// tells how many unused numbers are smaller than the `k`-th element.
+    pub fn from_index( dim: usize, n: usize, idx: usize ) -> Option<Vec<usize>> {
+        unrank( dim, n, idx as u128 )
+    }
+
+    /// Returns the position of `perm` in the order of the iterator, that is, the inverse of
+    /// `Permutation::from_index`.
+    ///
+    /// Returns `None` if `perm` is not a permutation of numbers less than `n`
+    /// or the index does not fit in `usize`.
+    pub fn to_index( perm: &[usize], n: usize ) -> Option<usize> {
+        let mut idx: usize = 0;
+
+        for (k, &x) in perm.iter().enumerate() {
+            if x >= n || perm[..k].contains(&x) {
+                return None;
             }
+            let smaller = perm[..k].iter().filter(|&&y| y < x).count();
+            idx = idx.checked_mul(n - k)?.checked_add(x - smaller)?;
         }
 
-        false
+        Some(idx)
+    }
+
+    /// Moves the iterator so that the next call of `next()` returns the `idx`-th permutation,
+    /// without generating the ones in between.
+    pub fn seek( &mut self, idx: u128 ) {
+        let (dim, n) = (self.dim, self.n);
+
+        self.cursor.seek( idx, || {
+            let mut state = unrank( dim, n, idx )?;
+            let mut used = vec![ false; n ];
+            for &x in state.iter() {
+                used[x] = true;
+            }
+            state.extend( (0..n).filter(|&x| !used[x]) );
+            Some(state)
+        });
+    }
+
+    /// Position of the permutation which the next call of `next()` returns.
+    pub fn next_index(&self) -> u128 {
+        self.cursor.next_index()
+    }
+
+    /// Calls `f` on each remaining permutation by reference without allocating, until `f` returns
+    /// `ControlFlow::Break`. The iterator can be resumed after it.
+    pub fn for_each_slice<B, F>( &mut self, f: F ) -> ControlFlow<B>
+        where F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        let dim = self.dim;
+        self.cursor.for_each(|state| shift( state, dim, |a, b| a < b ), f)
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
+
+    fn step(&mut self) {
+        let dim = self.dim;
+        self.cursor.step(|state| shift( state, dim, |a, b| a < b ));
+    }
+
+    fn step_back(&mut self) {
+        let (dim, n) = (self.dim, self.n);
+        self.cursor.step_back(|| (0..n).rev().collect(), |state| shift( state, dim, |a, b| a > b ));
     }
 }
 
 
-#[cfg(feature = "streaming")]
-mod streaming_iterator {
-    use super::{Permutation, Status};
-    use streaming_iterator::StreamingIterator;
+/// Same as `Permutation::from_index`, with the index as `u128`.
+fn unrank( dim: usize, n: usize, idx: u128 ) -> Option<Vec<usize>> {
+    if dim > n {
+        return None;
+    }
 
-    impl StreamingIterator for Permutation {
-        type Item = [usize];
+    let mut idx = idx;
+    let mut digits = vec![ 0; dim ];
+    for k in (0..dim).rev() {
+        digits[k] = (idx % (n - k) as u128) as usize;
+        idx /= (n - k) as u128;
+    }
+    if idx != 0 {
+        return None;
+    }
 
-        fn advance(&mut self) {
-            match self.status {
-                Status::Ini => { self.status = Status::Run; },
-                Status::Run => {
-                    if self.state == self.end {
-                        self.status = Status::End;
-                    } else { while {
-                        self.state[self.dim-1] += 1;
-                        
-                        for idx in (1..self.dim).rev() {
-                            if self.state[idx] == self.n {
-                                self.state[idx] = 0;
-                                self.state[idx-1] += 1;
-                            }
-                        }
-                        
-                        self.has_duplication()
-                    } {} }
+    let mut vec = Vec::with_capacity(dim);
+    let mut used: Vec<usize> = Vec::with_capacity(dim);
+    for d in digits {
+        // 未使用の数のうち d 番目に小さいもの
+        let mut x = d;
+        for &u in used.iter() {
+            if u <= x { x += 1; } else { break; }
+        }
+        let pos = used.binary_search(&x).unwrap_err();
+        used.insert(pos, x);
+        vec.push(x);
+    }
+
+    Some(vec)
+}
+
+/// Generates all permutations of `0..n` by Heap's algorithm, where each permutation
+/// differs from the previous one by a single swap.
+#[derive(Debug, Clone)]
+pub struct Heap {
+    state: Vec<usize>,
+    /// Counters of the algorithm, `c[i] <= i`.
+    c: Vec<usize>,
+    i: usize,
+    swapped: Option<(usize, usize)>,
+    ok: bool,
+    taken: u128,
+}
+
+impl Heap {
+    /// Generates all permutations of `0..n`, starting from `[0, 1, ..., n-1]`.
+    pub fn new( n: usize ) -> Heap {
+        Heap { state: (0..n).collect(), c: vec![0; n], i: 1, swapped: None, ok: false, taken: 0 }
+    }
+
+    /// Number of all permutations, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::factorial_u128( self.state.len() )
+    }
+
+    /// Number of permutations not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.total().map(|total| total - self.taken)
+    }
+
+    /// Positions `(i, j)` with `i < j` swapped to reach the current permutation
+    /// from the previous one, or `None` for the first one.
+    pub fn swapped(&self) -> Option<(usize, usize)> {
+        self.swapped
+    }
+
+    /// Calls `f` on each remaining permutation by reference without allocating, until `f` returns
+    /// `ControlFlow::Break`. The iterator can be resumed after it.
+    pub fn for_each_slice<B, F>( &mut self, mut f: F ) -> ControlFlow<B>
+        where F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        loop {
+            self.step();
+            if !self.ok {
+                return ControlFlow::Continue(());
+            }
+            if let ControlFlow::Break(b) = f( &self.state ) {
+                return ControlFlow::Break(b);
+            }
+        }
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
+
+    fn step(&mut self) {
+        if self.taken == 0 {
+            self.ok = true;
+            self.taken = 1;
+            return;
+        }
+        if !self.ok {
+            return;
+        }
+
+        let n = self.state.len();
+        while self.i < n {
+            let i = self.i;
+            if self.c[i] < i {
+                let j = if i & 1 == 0 { 0 } else { self.c[i] };
+                self.state.swap(j, i);
+                self.swapped = Some((j, i));
+                self.c[i] += 1;
+                self.i = 1;
+                self.taken += 1;
+                return;
+            }
+            self.c[i] = 0;
+            self.i += 1;
+        }
+        self.ok = false;
+    }
+}
+
+
+/// Generates all permutations of `0..n` by the Steinhaus-Johnson-Trotter algorithm
+/// (plain changes), where each permutation differs from the previous one by swapping
+/// two adjacent elements.
+#[doc(alias = "SJT")]
+#[derive(Debug, Clone)]
+pub struct PlainChanges {
+    state: Vec<usize>,
+    /// Number of inversions of each element `1..=n`, as in Knuth's Algorithm P.
+    c: Vec<usize>,
+    /// Whether `c[j]` is increasing.
+    up: Vec<bool>,
+    swapped: Option<(usize, usize)>,
+    ok: bool,
+    taken: u128,
+}
+
+impl PlainChanges {
+    /// Generates all permutations of `0..n`, starting from `[0, 1, ..., n-1]`.
+    pub fn new( n: usize ) -> PlainChanges {
+        PlainChanges {
+            state: (0..n).collect(),
+            c: vec![0; n+1],
+            up: vec![true; n+1],
+            swapped: None, ok: false, taken: 0,
+        }
+    }
+
+    /// Number of all permutations, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::factorial_u128( self.state.len() )
+    }
+
+    /// Number of permutations not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.total().map(|total| total - self.taken)
+    }
+
+    /// Positions `(i, i+1)` swapped to reach the current permutation from the previous one,
+    /// or `None` for the first one.
+    pub fn swapped(&self) -> Option<(usize, usize)> {
+        self.swapped
+    }
+
+    /// Calls `f` on each remaining permutation by reference without allocating, until `f` returns
+    /// `ControlFlow::Break`. The iterator can be resumed after it.
+    pub fn for_each_slice<B, F>( &mut self, mut f: F ) -> ControlFlow<B>
+        where F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        loop {
+            self.step();
+            if !self.ok {
+                return ControlFlow::Continue(());
+            }
+            if let ControlFlow::Break(b) = f( &self.state ) {
+                return ControlFlow::Break(b);
+            }
+        }
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
+
+    fn step(&mut self) {
+        if self.taken == 0 {
+            self.ok = true;
+            self.taken = 1;
+            return;
+        }
+        if !self.ok {
+            return;
+        }
+
+        // 動かせる一番大きい要素を探す
+        let mut j = self.state.len();
+        let mut s = 0;
+        while j > 1 {
+            let c = self.c[j];
+            let q = if self.up[j] { Some(c + 1) } else { c.checked_sub(1) };
+            match q {
+                Some(q) if q < j => {
+                    let (x, y) = (j - c + s - 1, j - q + s - 1);
+                    self.state.swap(x, y);
+                    self.swapped = Some((x.min(y), x.max(y)));
+                    self.c[j] = q;
+                    self.taken += 1;
+                    return;
                 },
-                Status::End => {},
+                // 右端に着いたので、その分だけ左の要素の位置がずれる
+                Some(_) => s += 1,
+                None => {},
+            }
+            self.up[j] = !self.up[j];
+            j -= 1;
+        }
+        self.ok = false;
+    }
+}
+
+
+/// Generates the permutations of `0..n` without fixed points (derangements), or with exactly
+/// `r` fixed points, in lexicographic order.
+///
+/// They are generated directly rather than by filtering `Permutation`. A step takes `O(n^2)` time
+/// at worst, but usually touches only the last few positions.
+#[derive(Debug, Clone)]
+pub struct Derangement {
+    cursor: Cursor,
+    n: usize,
+    /// Number of fixed points.
+    r: usize,
+}
+
+impl Derangement {
+    /// Generates all permutations `p` of `0..n` such that `p[i] != i` for every `i`.
+    ///
+    /// There is exactly one (empty) derangement if `n == 0`, and none if `n == 1`.
+    pub fn new( n: usize ) -> Derangement {
+        Derangement::with_fixed_points( n, 0 )
+    }
+
+    /// Generates all permutations `p` of `0..n` such that `p[i] == i` for exactly `r` of the `i`s.
+    pub fn with_fixed_points( n: usize, r: usize ) -> Derangement {
+        Derangement {
+            cursor: Cursor::new( first_fixed( n, r ), n, count::rencontres_u128( n, r ) ),
+            n, r,
+        }
+    }
+
+    /// Number of fixed points of the permutations.
+    pub fn fixed_points(&self) -> usize {
+        self.r
+    }
+
+    /// Number of all permutations, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::rencontres_u128( self.n, self.r )
+    }
+
+    /// Number of permutations not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.cursor.remaining()
+    }
+
+    /// Returns the `idx`-th derangement (counting from 0) of `0..n` in the order of the iterator,
+    /// or `None` if there are not so many derangements.
+    pub fn from_index( n: usize, idx: usize ) -> Option<Vec<usize>> {
+        unrank_fixed( n, 0, idx as u128 )
+    }
+
+    /// Returns the position of `perm` in `Derangement::new( perm.len() )`, that is, the inverse of
+    /// `Derangement::from_index`.
+    ///
+    /// Returns `None` if `perm` is not a derangement of `0..perm.len()`
+    /// or the index does not fit in `usize`.
+    pub fn to_index( perm: &[usize] ) -> Option<usize> {
+        usize::try_from( rank_fixed( perm, 0 )? ).ok()
+    }
+
+    /// Same as `Derangement::from_index`, for `Derangement::with_fixed_points( n, r )`.
+    ///
+    /// This also returns `None` if the number of permutations does not fit in `u128`.
+    pub fn from_index_with_fixed_points( n: usize, r: usize, idx: u128 ) -> Option<Vec<usize>> {
+        unrank_fixed( n, r, idx )
+    }
+
+    /// Same as `Derangement::to_index`, for `Derangement::with_fixed_points( perm.len(), r )`.
+    ///
+    /// Returns `None` if `perm` is not a permutation of `0..perm.len()` with exactly `r` fixed
+    /// points or the number of permutations does not fit in `u128`.
+    pub fn to_index_with_fixed_points( perm: &[usize], r: usize ) -> Option<u128> {
+        rank_fixed( perm, r )
+    }
+
+    /// Moves the iterator so that the next call of `next()` returns the `idx`-th permutation,
+    /// without generating the ones in between.
+    pub fn seek( &mut self, idx: u128 ) {
+        let (n, r) = (self.n, self.r);
+        self.cursor.seek( idx, || unrank_fixed( n, r, idx ) );
+    }
+
+    /// Position of the permutation which the next call of `next()` returns.
+    pub fn next_index(&self) -> u128 {
+        self.cursor.next_index()
+    }
+
+    /// Calls `f` on each remaining permutation by reference without allocating, until `f` returns
+    /// `ControlFlow::Break`. The iterator can be resumed after it.
+    pub fn for_each_slice<B, F>( &mut self, f: F ) -> ControlFlow<B>
+        where F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        let r = self.r;
+        self.cursor.for_each(|state| shift_fixed( state, r, true ), f)
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
+
+    fn step(&mut self) {
+        let r = self.r;
+        self.cursor.step(|state| shift_fixed( state, r, true ));
+    }
+
+    fn step_back(&mut self) {
+        let (n, r) = (self.n, self.r);
+        self.cursor.step_back(|| last_fixed( n, r ), |state| shift_fixed( state, r, false ));
+    }
+}
+
+
+/// Whether `m` numbers can be put at `m` positions with exactly `f` of them fixed,
+/// where `c` of the positions have their own numbers among them.
+fn completable( m: usize, c: usize, f: usize ) -> bool {
+    // 残りの c-f 個の候補のうち、1 個だけが残ると自分の位置にしか置けない
+    f <= c && !(m - f == 1 && c - f == 1)
+}
+
+/// Number of positions `from..` whose own numbers are in `state[from..]`.
+fn own( state: &[usize], from: usize ) -> usize {
+    state[from..].iter().filter(|&&x| x >= from).count()
+}
+
+/// The first permutation of `0..n` with `r` fixed points in lexicographic order.
+fn first_fixed( n: usize, r: usize ) -> Vec<usize> {
+    let mut state: Vec<usize> = (0..n).collect();
+    // 存在しなければ中身は使われない
+    if completable( n, n, r ) {
+        fill_fixed( &mut state, 0, r, true );
+    }
+    state
+}
+
+/// The last permutation of `0..n` with `r` fixed points in lexicographic order.
+fn last_fixed( n: usize, r: usize ) -> Vec<usize> {
+    let mut state: Vec<usize> = (0..n).collect();
+    if completable( n, n, r ) {
+        fill_fixed( &mut state, 0, r, false );
+    }
+    state
+}
+
+/// Rearranges `state[from..]` into the lexicographically smallest (or largest if not `smallest`)
+/// arrangement with exactly `f` fixed points. It must exist.
+fn fill_fixed( state: &mut [usize], from: usize, f: usize, smallest: bool ) {
+    let n = state.len();
+    if smallest {
+        state[from..].sort_unstable();
+    } else {
+        state[from..].sort_unstable_by(|a, b| b.cmp(a));
+    }
+
+    let (mut f, mut c) = (f, own( state, from ));
+    for j in from..n {
+        let has_own = state[j..].contains(&j) as usize;
+        // 並んだ順に試して、残りを並べられる最初の数を j に置く
+        for t in j..n {
+            let x = state[t];
+            let fixed = (x == j) as usize;
+            let rest = c - has_own - (x > j) as usize;
+            if fixed <= f && completable( n - j - 1, rest, f - fixed ) {
+                state[j..t+1].rotate_right(1);
+                f -= fixed;
+                c = rest;
+                break;
             }
         }
+    }
+}
+
+/// Rearranges `state` into the next permutation with `r` fixed points in lexicographic order
+/// if `forward`, or the previous one otherwise. Returns `false` if there is no such permutation.
+fn shift_fixed( state: &mut [usize], r: usize, forward: bool ) -> bool {
+    let n = state.len();
+    let mut fixed = r;
+
+    for i in (0..n).rev() {
+        // fixed は state[..i] の不動点の数
+        if state[i] == i {
+            fixed -= 1;
+        }
+        let f = r - fixed;
+        let c = own( state, i );
+        let has_own = state[i..].contains(&i) as usize;
+
+        // 残りを並べられる数のうち、state[i] の次に大きい (小さい) ものを一度に探す
+        let cur = state[i];
+        let ok = |x: usize| {
+            let fx = (x == i) as usize;
+            fx <= f && completable( n - i - 1, c - has_own - (x > i) as usize, f - fx )
+        };
+        let candidates = (i+1..n).filter(|&t| (if forward { state[t] > cur } else { state[t] < cur }) && ok(state[t]));
+        let next = if forward {
+            candidates.min_by_key(|&t| state[t])
+        } else {
+            candidates.max_by_key(|&t| state[t])
+        };
+
+        if let Some(t) = next {
+            let fx = (state[t] == i) as usize;
+            state.swap( i, t );
+            fill_fixed( state, i + 1, f - fx, forward );
+            return true;
+        }
+    }
+
+    false
+}
+
+/// `table[a][b]` is the number of ways to put `a` numbers at `a` positions without fixed points,
+/// where `b` of the positions have their own numbers among them, for `a <= m`.
+fn derangement_table( m: usize ) -> Option<Vec<Vec<u128>>> {
+    let mut table: Vec<Vec<u128>> = Vec::with_capacity(m + 1);
+
+    for a in 0..m+1 {
+        let mut row = Vec::with_capacity(a + 1);
+        row.push( count::factorial_u128( a )? );
+        for b in 1..a+1 {
+            // b 番目の位置に自分の数が来るものを除く
+            row.push( row[b-1] - table[a-1][b-1] );
+        }
+        table.push(row);
+    }
+
+    Some(table)
+}
+
+/// Number of ways to put `m` numbers at `m` positions with exactly `f` of them fixed,
+/// where `c` of the positions have their own numbers among them.
+fn completions( table: &[Vec<u128>], m: usize, c: usize, f: usize ) -> Option<u128> {
+    if f > c || table[m - f][c - f] == 0 {
+        return Some(0);
+    }
+
+    count::binomial_u128( c, f )?.checked_mul(table[m - f][c - f])
+}
+
+/// Same as `Derangement::from_index_with_fixed_points`.
+fn unrank_fixed( n: usize, r: usize, idx: u128 ) -> Option<Vec<usize>> {
+    if idx >= count::rencontres_u128( n, r )? {
+        return None;
+    }
+
+    let table = derangement_table( n - r )?;
+    let mut used = vec![ false; n ];
+    let (mut idx, mut f, mut c) = (idx, r, n);
+    let mut perm = Vec::with_capacity(n);
+
+    for j in 0..n {
+        let has_own = !used[j] as usize;
+        // 小さい数から順に、それを j に置いたときの並べ方の数を引いていく
+        for (x, u) in used.iter_mut().enumerate() {
+            if *u {
+                continue;
+            }
+            let fx = (x == j) as usize;
+            let rest = c - has_own - (x > j) as usize;
+            let k = if fx <= f { completions( &table, n - j - 1, rest, f - fx )? } else { 0 };
+            if idx < k {
+                perm.push(x);
+                *u = true;
+                f -= fx;
+                c = rest;
+                break;
+            }
+            idx -= k;
+        }
+    }
+
+    Some(perm)
+}
+
+/// Same as `Derangement::to_index_with_fixed_points`.
+fn rank_fixed( perm: &[usize], r: usize ) -> Option<u128> {
+    let n = perm.len();
+    let mut used = vec![ false; n ];
+    for &x in perm.iter() {
+        if x >= n || used[x] {
+            return None;
+        }
+        used[x] = true;
+    }
+    if perm.iter().enumerate().filter(|&(i, &x)| i == x).count() != r {
+        return None;
+    }
+
+    count::rencontres_u128( n, r )?;
+    let table = derangement_table( n - r )?;
+    let mut used = vec![ false; n ];
+    let (mut idx, mut f, mut c) = (0, r, n);
+
+    for (j, &x) in perm.iter().enumerate() {
+        let has_own = !used[j] as usize;
+        // x より小さい数を j に置いたものが前にある
+        for y in (0..x).filter(|&y| !used[y]) {
+            let fy = (y == j) as usize;
+            if fy <= f {
+                idx += completions( &table, n - j - 1, c - has_own - (y > j) as usize, f - fy )?;
+            }
+        }
+        used[x] = true;
+        f -= (x == j) as usize;
+        c -= has_own + (x > j) as usize;
+    }
+
+    Some(idx)
+}
+
+
+mod streaming {
+    use super::{Permutation, Heap, PlainChanges, Derangement};
+    use crate::count;
+    use crate::streaming::{Source, SourceBack};
+
+    impl Source for Permutation {
+        type Item = [usize];
+
+        fn advance(&mut self) {
+            self.step();
+        }
         
         fn get(&self) -> Option<&[usize]> {
-            match self.status {
-                Status::Ini => None,
-                Status::Run => Some(&self.state),
-                Status::End => None,
-            }
+            self.cursor.current()
+        }
+
+        fn jump(&mut self, k: usize) {
+            self.seek( self.next_index().saturating_add(k as u128) );
         }
 
         fn size_hint(&self) -> (usize,Option<usize>) {
-            let size = (self.n-self.dim+1..self.n+1).fold(1, |prod, i| prod * i);
-            ( 0, Some(size) )
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl SourceBack for Permutation {
+        fn advance_back(&mut self) {
+            self.step_back();
+        }
+    }
+
+    impl Source for Derangement {
+        type Item = [usize];
+
+        fn advance(&mut self) {
+            self.step();
+        }
+
+        fn get(&self) -> Option<&[usize]> {
+            self.cursor.current()
+        }
+
+        fn jump(&mut self, k: usize) {
+            self.seek( self.next_index().saturating_add(k as u128) );
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl SourceBack for Derangement {
+        fn advance_back(&mut self) {
+            self.step_back();
+        }
+    }
+
+    impl Source for Heap {
+        type Item = [usize];
+
+        fn advance(&mut self) {
+            self.step();
+        }
+
+        fn get(&self) -> Option<&[usize]> {
+            if self.ok { Some(&self.state) } else { None }
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.remaining() )
+        }
+    }
+
+    impl Source for PlainChanges {
+        type Item = [usize];
+
+        fn advance(&mut self) {
+            self.step();
+        }
+
+        fn get(&self) -> Option<&[usize]> {
+            if self.ok { Some(&self.state) } else { None }
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.remaining() )
         }
     }
 }
 
 
-#[cfg(not(feature = "streaming"))]
 mod iterator {
-    use super::{Permutation, Status};
-    use std::iter::Iterator;
+    use super::{Permutation, Heap, PlainChanges, Derangement};
+    use crate::count;
+    use alloc::vec::Vec;
+    use core::iter::{Iterator, DoubleEndedIterator};
     
     impl Iterator for Permutation {
         type Item = Vec<usize>;
         
         fn next(&mut self) -> Option<Vec<usize>> {
-            match self.status {
-                Status::Ini => { self.status = Status::Run; },
-                Status::Run => {
-                    if self.state == self.end {
-                        self.status = Status::End;
-                    } else { while {
-                        self.state[self.dim-1] += 1;
-            
-                        for idx in (1..self.dim).rev() {
-                            if self.state[idx] == self.n {
-                                self.state[idx] = 0;
-                                self.state[idx-1] += 1;
-                            }
-                        }
-                        
-                        self.has_duplication()
-                    } {} }
-                },
-                Status::End => {},
-            }
-            
-            match self.status {
-                Status::Ini => None,
-                Status::Run => Some(self.state.clone()),
-                Status::End => None,
-            }
+            self.step();
+            self.cursor.front().map(|perm| perm.to_vec())
+        }
+
+        fn nth(&mut self, k: usize) -> Option<Vec<usize>> {
+            self.seek( self.next_index().saturating_add(k as u128) );
+            self.next()
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl DoubleEndedIterator for Permutation {
+        fn next_back(&mut self) -> Option<Vec<usize>> {
+            self.step_back();
+            self.cursor.back().map(|perm| perm.to_vec())
+        }
+    }
+
+    impl Iterator for Heap {
+        type Item = Vec<usize>;
+
+        fn next(&mut self) -> Option<Vec<usize>> {
+            self.step();
+            if self.ok { Some(self.state.clone()) } else { None }
         }
 
         fn size_hint(&self) -> (usize,Option<usize>) {
-            let size = (self.n-self.dim+1..self.n+1).fold(1, |prod, i| prod * i);
-            ( 0, Some(size) )
+            count::size_hint( self.remaining() )
+        }
+    }
+
+    impl Iterator for PlainChanges {
+        type Item = Vec<usize>;
+
+        fn next(&mut self) -> Option<Vec<usize>> {
+            self.step();
+            if self.ok { Some(self.state.clone()) } else { None }
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.remaining() )
+        }
+    }
+
+    impl Iterator for Derangement {
+        type Item = Vec<usize>;
+
+        fn next(&mut self) -> Option<Vec<usize>> {
+            self.step();
+            self.cursor.front().map(|perm| perm.to_vec())
+        }
+
+        fn nth(&mut self, k: usize) -> Option<Vec<usize>> {
+            self.seek( self.next_index().saturating_add(k as u128) );
+            self.next()
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl DoubleEndedIterator for Derangement {
+        fn next_back(&mut self) -> Option<Vec<usize>> {
+            self.step_back();
+            self.cursor.back().map(|perm| perm.to_vec())
         }
     }
 }
 
 
+#[cfg(feature = "rand")]
+mod sample {
+    use super::Permutation;
+    use alloc::vec::Vec;
+    use rand::Rng;
+
+    impl Permutation {
+        /// Returns a permutation of `dim` numbers out of `0..n` chosen uniformly at random,
+        /// or `None` if `dim > n`.
+        ///
+        /// This is a partial Fisher–Yates shuffle of `0..n` which remembers only the swapped
+        /// positions, so it takes `O(dim^2)` time and `O(dim)` memory regardless of `n`.
+        pub fn sample<R: Rng + ?Sized>( dim: usize, n: usize, rng: &mut R ) -> Option<Vec<usize>> {
+            if dim > n {
+                return None;
+            }
+
+            // (位置, 数) を位置の順に並べたもの。ここにない位置 p には p がある
+            let mut moved: Vec<(usize, usize)> = Vec::with_capacity(dim);
+            let mut perm = Vec::with_capacity(dim);
+            for i in 0..dim {
+                let j = rng.gen_range( i..n );
+                // 位置 i は以後使わないので取り除く。i は残っている位置のうち最小
+                let x = match moved.first() {
+                    Some(&(p, x)) if p == i => { moved.remove(0); x },
+                    _ => i,
+                };
+                if j == i {
+                    perm.push(x);
+                    continue;
+                }
+                match moved.binary_search_by_key( &j, |&(p, _)| p ) {
+                    Ok(k) => perm.push( core::mem::replace( &mut moved[k].1, x ) ),
+                    Err(k) => {
+                        moved.insert( k, (j, x) );
+                        perm.push(j);
+                    },
+                }
+            }
+            Some(perm)
+        }
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -148,6 +888,7 @@ mod tests {
         let mut iter = Permutation::new( dim, n );
         let mut count = 0;
 
+        #[allow(clippy::while_let_on_iterator)]
         while let Some(idx) = iter.next() {
             count += 1;
             println!("{:?}", idx);
@@ -190,5 +931,412 @@ mod tests {
         assert_eq!( iter.next().unwrap(), &[3,2,1] );
         assert_eq!( iter.next(), None );
     }
+
+
+    #[test]
+    fn same_as_filtered_sequence() {
+        for n in 1..7 {
+            for dim in 1..n+1 {
+                let mut expected = Vec::new();
+                let mut iter = Sequence::new( dim, n );
+                for seq in iter.by_ref() {
+                    let mut sorted = seq.to_vec();
+                    sorted.sort();
+                    sorted.dedup();
+                    if sorted.len() == dim {
+                        expected.push( seq.to_vec() );
+                    }
+                }
+
+                let mut actual = Vec::new();
+                let mut iter = Permutation::new( dim, n );
+                for perm in iter.by_ref() {
+                    actual.push( perm.to_vec() );
+                }
+
+                assert_eq!( actual, expected );
+            }
+        }
+    }
+
+
+    #[test]
+    fn from_index() {
+        let dim = 3;
+        let n = 5;
+
+        let mut iter = Permutation::new( dim, n );
+        let mut idx = 0;
+
+        for elem in iter.by_ref() {
+            let vec = Permutation::from_index( dim, n, idx ).unwrap();
+            let vec: &[usize] = &vec;
+            assert_eq!( elem, vec );
+            assert_eq!( Permutation::to_index( vec, n ), Some(idx) );
+            idx += 1;
+        }
+
+        assert_eq!( Permutation::from_index( dim, n, idx ), None );
+        assert_eq!( Permutation::to_index( &[0,0,1], n ), None );
+        assert_eq!( Permutation::to_index( &[0,1,5], n ), None );
+    }
+
+
+    #[test]
+    fn size_hint() {
+        let mut iter = Permutation::new( 3, 4 );
+        assert_eq!( iter.total(), Some(24) );
+
+        for rest in (0..24).rev() {
+            assert_eq!( iter.size_hint(), (rest+1, Some(rest+1)) );
+            iter.next().unwrap();
+            assert_eq!( iter.remaining(), Some(rest as u128) );
+        }
+
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.size_hint(), (0, Some(0)) );
+        let iter = Permutation::new( 40, 40 );
+        assert_eq!( iter.total(), None );
+        assert_eq!( iter.remaining(), None );
+        assert_eq!( iter.size_hint(), (usize::MAX, None) );
+        let iter = Permutation::new( 25, 25 );
+        assert_eq!( iter.remaining(), Some(count::factorial_u128( 25 ).unwrap()) );
+        assert_eq!( iter.size_hint(), (usize::MAX, None) );
+    }
+
+
+    #[test]
+    fn next_back() {
+        let dim = 3;
+        let n = 5;
+
+        let mut forward = Vec::new();
+        let mut iter = Permutation::new( dim, n );
+        for elem in iter.by_ref() {
+            forward.push( elem.to_vec() );
+        }
+
+        let mut backward = Vec::new();
+        let mut iter = Permutation::new( dim, n );
+        while let Some(elem) = iter.next_back() {
+            backward.push( elem.to_vec() );
+        }
+        backward.reverse();
+        assert_eq!( backward, forward );
+
+        // 両端から交互に取り出す
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        let mut iter = Permutation::new( dim, n );
+        while let Some(elem) = iter.next() {
+            front.push( elem.to_vec() );
+            match iter.next_back() {
+                Some(elem) => back.push( elem.to_vec() ),
+                None => break,
+            }
+        }
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.next_back(), None );
+        back.reverse();
+        front.append( &mut back );
+        assert_eq!( front, forward );
+    }
+
+
+    #[test]
+    fn seek() {
+        let dim = 3;
+        let n = 5;
+
+        let mut all = Vec::new();
+        let mut iter = Permutation::new( dim, n );
+        for elem in iter.by_ref() {
+            all.push( elem.to_vec() );
+        }
+
+        for (idx, elem) in all.iter().enumerate() {
+            let mut iter = Permutation::new( dim, n );
+            iter.seek( idx as u128 );
+            assert_eq!( iter.next().unwrap(), &elem[..] );
+            assert_eq!( iter.next_index(), idx as u128 + 1 );
+        }
+
+        let mut iter = Permutation::new( dim, n );
+        assert_eq!( iter.nth(3).unwrap(), &all[3][..] );
+        assert_eq!( iter.nth(2).unwrap(), &all[6][..] );
+        assert_eq!( iter.nth(all.len()), None );
+        iter.seek( 1 );
+        assert_eq!( iter.next().unwrap(), &all[1][..] );
+
+        // 後ろから取り出したものには飛ばない
+        let mut iter = Permutation::new( dim, n );
+        iter.next_back();
+        iter.seek( all.len() as u128 - 1 );
+        assert_eq!( iter.next(), None );
+
+        // 添字が usize に収まらない場合
+        let mut iter = Permutation::new( 30, 30 );
+        let idx = iter.total().unwrap() / 3;
+        iter.seek( idx );
+        let elem = iter.next().unwrap().to_vec();
+        let next = iter.next().unwrap().to_vec();
+        iter.seek( idx + 1 );
+        assert_eq!( iter.next().unwrap(), &next[..] );
+        assert_ne!( elem, next );
+    }
+
+
+    #[test]
+    fn edge_cases() {
+        // 空の列がちょうど 1 つ
+        for n in 0..3 {
+            let mut iter = Permutation::new( 0, n );
+            assert_eq!( iter.total(), Some(1) );
+            assert_eq!( iter.next().unwrap(), &[] );
+            assert_eq!( iter.next(), None );
+            assert_eq!( iter.next_back(), None );
+
+            let mut iter = Permutation::new( 0, n );
+            assert_eq!( iter.next_back().unwrap(), &[] );
+            assert_eq!( iter.next(), None );
+            assert!( Permutation::try_new( 0, n ).is_ok() );
+        }
+
+        let mut iter = Permutation::new( 4, 3 );
+        assert_eq!( iter.total(), Some(0) );
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.next_back(), None );
+        assert_eq!( Permutation::try_new( 4, 3 ).unwrap_err(), Error::DimTooLarge { dim: 4, n: 3 } );
+    }
+
+
+    #[test]
+    fn heap() {
+        let mut iter = permutation::Heap::new( 3 );
+        let mut prev: Vec<usize> = Vec::new();
+        let mut all = Vec::new();
+        assert_eq!( iter.size_hint(), (6, Some(6)) );
+        while let Some(perm) = iter.next() {
+            let perm = perm.to_vec();
+            match iter.swapped() {
+                Some((i, j)) => { prev.swap(i, j); assert_eq!( prev, perm ); },
+                None => assert!( prev.is_empty() ),
+            }
+            prev = perm.clone();
+            all.push(perm);
+        }
+        assert_eq!( all, vec![
+            vec![0,1,2], vec![1,0,2], vec![2,0,1], vec![0,2,1], vec![1,2,0], vec![2,1,0],
+        ]);
+        assert_eq!( iter.remaining(), Some(0) );
+
+        let mut iter = permutation::Heap::new( 6 );
+        let mut all = Vec::new();
+        for perm in iter.by_ref() {
+            all.push(perm.to_vec());
+        }
+        all.sort();
+        all.dedup();
+        assert_eq!( all.len(), 720 );
+
+        let iter = permutation::Heap::new( 25 );
+        assert_eq!( iter.remaining(), count::factorial_u128( 25 ) );
+        assert_eq!( iter.size_hint(), (usize::MAX, None) );
+    }
+
+
+    #[test]
+    fn plain_changes() {
+        let mut iter = permutation::PlainChanges::new( 3 );
+        let mut prev: Vec<usize> = Vec::new();
+        let mut all = Vec::new();
+        while let Some(perm) = iter.next() {
+            let perm = perm.to_vec();
+            match iter.swapped() {
+                Some((i, j)) => { assert_eq!( i+1, j ); prev.swap(i, j); assert_eq!( prev, perm ); },
+                None => assert!( prev.is_empty() ),
+            }
+            prev = perm.clone();
+            all.push(perm);
+        }
+        assert_eq!( all, vec![
+            vec![0,1,2], vec![0,2,1], vec![2,0,1], vec![2,1,0], vec![1,2,0], vec![1,0,2],
+        ]);
+
+        let mut iter = permutation::PlainChanges::new( 6 );
+        let mut all = Vec::new();
+        let mut prev: Vec<usize> = Vec::new();
+        while let Some(perm) = iter.next() {
+            let perm = perm.to_vec();
+            if let Some((i, j)) = iter.swapped() {
+                assert_eq!( i+1, j );
+                prev.swap(i, j);
+                assert_eq!( prev, perm );
+            }
+            prev = perm.clone();
+            all.push(perm);
+        }
+        all.sort();
+        all.dedup();
+        assert_eq!( all.len(), 720 );
+
+        for n in 0..2 {
+            let mut iter = permutation::PlainChanges::new( n );
+            assert_eq!( iter.next().unwrap().len(), n );
+            assert_eq!( iter.next(), None );
+            let mut iter = permutation::Heap::new( n );
+            assert_eq!( iter.next().unwrap().len(), n );
+            assert_eq!( iter.next(), None );
+        }
+    }
+
+
+    #[test]
+    fn for_each_slice() {
+        use std::ops::ControlFlow;
+
+        let mut iter = Permutation::new( 2, 4 );
+        assert_eq!( iter.next_back().unwrap(), &[3,2] );
+        let mut all = Vec::new();
+        let flow = iter.for_each_slice(|perm| {
+            all.push(perm.to_vec());
+            if perm == [1,3] { ControlFlow::Break(all.len()) } else { ControlFlow::Continue(()) }
+        });
+        assert_eq!( flow, ControlFlow::Break(6) );
+        assert_eq!( all[3], &[1,0] );
+
+        // 途中から再開できる
+        assert_eq!( iter.next().unwrap(), &[2,0] );
+        let mut count = 0;
+        let flow: ControlFlow<()> = iter.for_each_slice(|_| { count += 1; ControlFlow::Continue(()) });
+        assert_eq!( (flow, count), (ControlFlow::Continue(()), 4) );
+        assert_eq!( iter.next(), None );
+
+        let mut count = 0;
+        let _: ControlFlow<()> = permutation::Heap::new( 4 ).for_each_slice(|_| { count += 1; ControlFlow::Continue(()) });
+        let _: ControlFlow<()> = permutation::PlainChanges::new( 4 ).for_each_slice(|_| { count += 1; ControlFlow::Continue(()) });
+        assert_eq!( count, 48 );
+    }
+
+    #[test]
+    fn derangement() {
+        use permutation::Derangement;
+
+        for n in 0..8 {
+            let all: Vec<Vec<usize>> = Permutation::new( n, n ).collect();
+            for r in 0..n+2 {
+                let expected: Vec<Vec<usize>> = all.iter()
+                    .filter(|perm| perm.iter().enumerate().filter(|&(i, &x)| i == x).count() == r)
+                    .cloned().collect();
+                let iter = Derangement::with_fixed_points( n, r );
+                assert_eq!( iter.size_hint(), (expected.len(), Some(expected.len())) );
+                assert_eq!( iter.clone().collect::<Vec<_>>(), expected );
+
+                let mut backward: Vec<Vec<usize>> = iter.rev().collect();
+                backward.reverse();
+                assert_eq!( backward, expected );
+            }
+        }
+
+        let mut iter = Derangement::new( 4 );
+        assert_eq!( iter.next().unwrap(), &[1,0,3,2] );
+        assert_eq!( iter.next().unwrap(), &[1,2,3,0] );
+        assert_eq!( iter.next_back().unwrap(), &[3,2,1,0] );
+        assert_eq!( iter.size_hint(), (6, Some(6)) );
+        assert_eq!( Derangement::new( 1 ).next(), None );
+        assert_eq!( Derangement::new( 0 ).collect::<Vec<_>>(), vec![ vec![] ] );
+
+        let iter = Derangement::new( 25 );
+        assert_eq!( iter.remaining(), count::subfactorial_u128( 25 ) );
+        assert_eq!( iter.size_hint(), (usize::MAX, None) );
+
+        // 総数が u128 に収まらなくても順に生成できる
+        let mut iter = Derangement::new( 40 );
+        assert_eq!( iter.total(), None );
+        let first = iter.next().unwrap();
+        assert_eq!( first[..4], [1,0,3,2] );
+        assert_eq!( iter.next_back().unwrap(), (0..40).rev().collect::<Vec<_>>() );
+    }
+
+
+    #[test]
+    fn derangement_index() {
+        use permutation::Derangement;
+
+        for r in 0..4 {
+            let iter = Derangement::with_fixed_points( 7, r );
+            let total = iter.total().unwrap();
+            for (idx, perm) in iter.enumerate() {
+                assert_eq!( Derangement::from_index_with_fixed_points( 7, r, idx as u128 ).unwrap(), perm );
+                assert_eq!( Derangement::to_index_with_fixed_points( &perm, r ), Some(idx as u128) );
+            }
+            assert_eq!( Derangement::from_index_with_fixed_points( 7, r, total ), None );
+        }
+
+        assert_eq!( Derangement::from_index( 4, 8 ), Some(vec![3,2,1,0]) );
+        assert_eq!( Derangement::to_index( &[3,2,1,0] ), Some(8) );
+        assert_eq!( Derangement::from_index( 4, 9 ), None );
+        assert_eq!( Derangement::to_index( &[0,2,1] ), None );
+        assert_eq!( Derangement::to_index( &[1,1,0] ), None );
+        assert_eq!( Derangement::to_index_with_fixed_points( &[0,2,1], 1 ), Some(0) );
+
+        // 不動点が多ければ n が大きくても求まる
+        let perm = Derangement::from_index_with_fixed_points( 1000, 998, 0 ).unwrap();
+        assert_eq!( perm[998..], [999,998] );
+        let perm = Derangement::from_index_with_fixed_points( 1000, 998, 499499 ).unwrap();
+        assert_eq!( (perm[0], perm[1], perm[999]), (999, 1, 0) );
+        assert_eq!( Derangement::to_index_with_fixed_points( &perm, 998 ), Some(499499) );
+
+        let all: Vec<Vec<usize>> = Derangement::new( 6 ).collect();
+        let mut iter = Derangement::new( 6 );
+        iter.seek( 100 );
+        assert_eq!( iter.next().unwrap(), all[100] );
+        assert_eq!( iter.nth( 5 ).unwrap(), all[106] );
+        let mut stream = Derangement::new( 6 ).streaming();
+        assert_eq!( stream.nth( 3 ).unwrap(), &all[3][..] );
+        assert_eq!( stream.next_back().unwrap(), &all[264][..] );
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn sample() {
+        use rand::{Rng, SeedableRng, rngs::SmallRng};
+
+        let mut rng = SmallRng::seed_from_u64( 1 );
+        let mut hist = [0; 24];
+        for _ in 0..24000 {
+            let perm = Permutation::sample( 2, 5, &mut rng ).unwrap();
+            hist[ Permutation::to_index( &perm, 5 ).unwrap() ] += 1;
+        }
+        assert!( hist[..20].iter().all(|&c| 1000 < c && c < 1400), "{:?}", hist );
+        assert_eq!( hist[20..], [0; 4] );
+
+        assert_eq!( Permutation::sample( 0, 0, &mut rng ), Some(vec![]) );
+        assert_eq!( Permutation::sample( 3, 2, &mut rng ), None );
+
+        let mut hist = [0; 24];
+        for _ in 0..24000 {
+            let perm = Permutation::sample( 4, 4, &mut rng ).unwrap();
+            hist[ Permutation::to_index( &perm, 4 ).unwrap() ] += 1;
+        }
+        assert!( hist.iter().all(|&c| 800 < c && c < 1200), "{:?}", hist );
+
+        // 0..n をすべて並べてシャッフルするのと同じものが得られる
+        for seed in 0..100 {
+            let mut pool: Vec<usize> = (0..8).collect();
+            let mut rng = SmallRng::seed_from_u64( seed );
+            for i in 0..6 {
+                let j = rng.gen_range( i..8 );
+                pool.swap( i, j );
+            }
+            let mut rng = SmallRng::seed_from_u64( seed );
+            assert_eq!( Permutation::sample( 6, 8, &mut rng ).unwrap(), pool[..6] );
+        }
+
+        let perm = Permutation::sample( 5, usize::MAX, &mut rng ).unwrap();
+        let mut sorted = perm.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!( sorted.len(), 5 );
+    }
 }
-    
@@ -1,100 +1,401 @@
+use crate::{count, Error, Items, Streaming};
+use crate::cursor::Cursor;
+use core::ops::ControlFlow;
+use crate::successor::sequence::{next_state, prev_state};
+use alloc::vec;
+use alloc::vec::Vec;
+
 
 #[derive(Debug, Clone)]
 pub struct Sequence {
-    state: Vec<usize>,
-    init: bool,
-    dim: usize,
-    n: usize,
+    cursor: Cursor,
+    /// Number of choices for each position.
+    radices: Vec<usize>,
 }
 
 impl Sequence {
+    /// Generates all sequences of `dim` numbers out of `0..n`.
+    ///
+    /// There is exactly one (empty) sequence if `dim == 0`, and none if `n == 0` and `dim > 0`.
     pub fn new( dim: usize, n: usize ) -> Sequence {
-        Sequence { state: vec![ 0; dim], init: true, dim, n }
+        Sequence::with_radices( &vec![ n; dim ] )
     }
 
-    pub fn from_index( dim: usize, n: usize, idx: usize ) -> Option<Vec<usize>> {
-        if idx >= n.pow(dim as u32) {
-            None
+    /// Generates all sequences whose `i`-th number is taken out of `0..radices[i]`.
+    ///
+    /// There is exactly one (empty) sequence if `radices` is empty, and none if it contains 0.
+    pub fn with_radices( radices: &[usize] ) -> Sequence {
+        let dim = radices.len();
+        Sequence { cursor: Cursor::new( vec![ 0; dim ], dim, count::radix_product_u128( radices ) ), radices: radices.to_vec() }
+    }
+
+    /// Same as `Sequence::new`, but returns an error instead of an iterator generating
+    /// no sequences if `n == 0` and `dim > 0`.
+    pub fn try_new( dim: usize, n: usize ) -> Result<Self, Error> {
+        if n == 0 && dim > 0 {
+            Err( Error::EmptyRange { dim } )
         } else {
-            let mut idx = idx;
-            let mut vec = Vec::with_capacity(dim);
-            
-            for k in 0..dim {
-                let x: usize = idx / n.pow((dim - 1 - k) as u32);
-                vec.push( x );
-                idx -= x * n.pow((dim - 1 - k) as u32);
+            Ok( Sequence::new( dim, n ) )
+        }
+    }
+
+    /// Generates all sequences of `dim` elements of `items`, with repetition, as references.
+    ///
+    /// Same as `Sequence::new( dim, items.len() )` with each index `i` replaced by `&items[i]`.
+    pub fn of<T>( items: &[T], dim: usize ) -> Items<'_, T, Self> {
+        Items::new( items, Sequence::new( dim, items.len() ) )
+    }
+
+    /// Number of all sequences, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::radix_product_u128( &self.radices )
+    }
+
+    /// Number of sequences not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.cursor.remaining()
+    }
+
+    /// Returns the `idx`-th sequence (counting from 0) in the order of the iterator,
+    /// or `None` if there are not so many sequences.
+    pub fn from_index( dim: usize, n: usize, idx: usize ) -> Option<Vec<usize>> {
+        Sequence::from_index_u128( dim, n, idx as u128 )
+    }
+
+    /// Same as `Sequence::from_index`, but accepts an index beyond `usize`.
+    pub fn from_index_u128( dim: usize, n: usize, idx: u128 ) -> Option<Vec<usize>> {
+        Sequence::from_index_with_radices( &vec![ n; dim ], idx )
+    }
+
+    /// Same as `Sequence::from_index_u128`, for `Sequence::with_radices( radices )`.
+    pub fn from_index_with_radices( radices: &[usize], idx: u128 ) -> Option<Vec<usize>> {
+        if radices.contains(&0) {
+            return None;
+        }
+
+        let mut idx = idx;
+        let mut vec = vec![ 0; radices.len() ];
+
+        // 混合基数の各桁を下から求める
+        for (x, &r) in vec.iter_mut().zip(radices).rev() {
+            *x = (idx % r as u128) as usize;
+            idx /= r as u128;
+        }
+
+        if idx == 0 { Some(vec) } else { None }
+    }
+
+    /// Returns the position of `seq` in the order of the iterator, that is, the inverse of
+    /// `Sequence::from_index`.
+    ///
+    /// Returns `None` if `seq` contains a number not less than `n`
+    /// or the index does not fit in `usize`.
+    pub fn to_index( seq: &[usize], n: usize ) -> Option<usize> {
+        let idx = Sequence::to_index_u128( seq, n )?;
+        if idx > usize::MAX as u128 { None } else { Some(idx as usize) }
+    }
+
+    /// Same as `Sequence::to_index`, but returns the index as `u128`.
+    ///
+    /// Returns `None` if `seq` contains a number not less than `n`
+    /// or the index does not fit in `u128`.
+    pub fn to_index_u128( seq: &[usize], n: usize ) -> Option<u128> {
+        Sequence::to_index_with_radices( seq, &vec![ n; seq.len() ] )
+    }
+
+    /// Same as `Sequence::to_index_u128`, for `Sequence::with_radices( radices )`.
+    ///
+    /// Returns `None` also if `seq` and `radices` have different lengths.
+    pub fn to_index_with_radices( seq: &[usize], radices: &[usize] ) -> Option<u128> {
+        if seq.len() != radices.len() {
+            return None;
+        }
+
+        let mut idx: u128 = 0;
+
+        for (&x, &r) in seq.iter().zip(radices) {
+            if x >= r {
+                return None;
+            }
+            idx = idx.checked_mul(r as u128)?.checked_add(x as u128)?;
+        }
+
+        Some(idx)
+    }
+
+    /// Moves the iterator so that the next call of `next()` returns the `idx`-th sequence,
+    /// without generating the ones in between.
+    pub fn seek( &mut self, idx: u128 ) {
+        let radices = &self.radices;
+        self.cursor.seek( idx, || Sequence::from_index_with_radices( radices, idx ) );
+    }
+
+    /// Position of the sequence which the next call of `next()` returns.
+    pub fn next_index(&self) -> u128 {
+        self.cursor.next_index()
+    }
+
+    /// Calls `f` on each remaining sequence by reference without allocating, until `f` returns
+    /// `ControlFlow::Break`. The iterator can be resumed after it.
+    pub fn for_each_slice<B, F>( &mut self, f: F ) -> ControlFlow<B>
+        where F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        let radices = &self.radices;
+        self.cursor.for_each(|state| next_state( state, radices ), f)
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
+
+    fn step(&mut self) {
+        let radices = &self.radices;
+        self.cursor.step(|state| next_state( state, radices ));
+    }
+
+    fn step_back(&mut self) {
+        let radices = &self.radices;
+        // 0 を含むなら空で、中身は使われない
+        self.cursor.step_back(
+            || radices.iter().map(|r| r.saturating_sub(1)).collect(),
+            |state| prev_state( state, radices ),
+        );
+    }
+}
+
+
+/// Generates all sequences of `Sequence` in the reflected Gray code order,
+/// where each sequence differs from the previous one in a single coordinate by `+1` or `-1`.
+///
+/// This is Knuth's loopless Algorithm H, so each step takes constant time.
+/// `change()` reports which coordinate changed and in which direction.
+#[derive(Debug, Clone)]
+pub struct Gray {
+    state: Vec<usize>,
+    radices: Vec<usize>,
+    /// Coordinates which can change, from the last one.
+    coords: Vec<usize>,
+    /// Focus pointers; `focus[j]` refers to `coords[j]`.
+    focus: Vec<usize>,
+    /// Whether each of `coords` is increasing.
+    up: Vec<bool>,
+    change: Option<(usize, isize)>,
+    ok: bool,
+    taken: u128,
+}
+
+impl Gray {
+    /// Generates all sequences of `dim` numbers out of `0..n`, starting from `[0, 0, ..., 0]`.
+    pub fn new( dim: usize, n: usize ) -> Gray {
+        Gray::with_radices( &vec![ n; dim ] )
+    }
+
+    /// Generates all sequences of `Sequence::with_radices( radices )`, starting from `[0, 0, ..., 0]`.
+    pub fn with_radices( radices: &[usize] ) -> Gray {
+        // 基数 1 の座標は動かない
+        let coords: Vec<usize> = (0..radices.len()).rev().filter(|&i| radices[i] >= 2).collect();
+        Gray {
+            state: vec![ 0; radices.len() ],
+            radices: radices.to_vec(),
+            focus: (0..coords.len()+1).collect(),
+            up: vec![ true; coords.len() ],
+            coords,
+            change: None, ok: false, taken: 0,
+        }
+    }
+
+    /// Number of all sequences, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        count::radix_product_u128( &self.radices )
+    }
+
+    /// Number of sequences not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.total().map(|total| total - self.taken)
+    }
+
+    /// The coordinate changed to reach the current sequence from the previous one, and
+    /// the difference (`1` or `-1`), or `None` for the first one.
+    pub fn change(&self) -> Option<(usize, isize)> {
+        self.change
+    }
+
+    /// Calls `f` on each remaining sequence by reference without allocating, until `f` returns
+    /// `ControlFlow::Break`. The iterator can be resumed after it.
+    pub fn for_each_slice<B, F>( &mut self, mut f: F ) -> ControlFlow<B>
+        where F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        loop {
+            self.step();
+            if !self.ok {
+                return ControlFlow::Continue(());
             }
+            if let ControlFlow::Break(b) = f( &self.state ) {
+                return ControlFlow::Break(b);
+            }
+        }
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
 
-            Some(vec)
+    fn step(&mut self) {
+        if self.taken == 0 {
+            self.ok = self.total() != Some(0);
+            self.taken = if self.ok { 1 } else { 0 };
+            return;
         }
+        if !self.ok {
+            return;
+        }
+
+        let j = self.focus[0];
+        self.focus[0] = 0;
+        if j == self.coords.len() {
+            self.ok = false;
+            return;
+        }
+
+        let i = self.coords[j];
+        if self.up[j] {
+            self.state[i] += 1;
+            self.change = Some((i, 1));
+        } else {
+            self.state[i] -= 1;
+            self.change = Some((i, -1));
+        }
+        if self.state[i] == 0 || self.state[i] == self.radices[i] - 1 {
+            self.up[j] = !self.up[j];
+            self.focus[j] = self.focus[j+1];
+            self.focus[j+1] = j + 1;
+        }
+        self.taken += 1;
     }
 }
 
 
-#[cfg(feature = "streaming")]
-mod streaming_iterator {
-    use super::Sequence;
-    use streaming_iterator::StreamingIterator;
+mod streaming {
+    use super::{Sequence, Gray};
+    use crate::count;
+    use crate::streaming::{Source, SourceBack};
 
-    impl StreamingIterator for Sequence {
+    impl Source for Sequence {
         type Item = [usize];
 
         fn advance(&mut self) {
-            if self.init {
-                self.init = false;
-            } else {
-                self.state[self.dim-1] += 1;
-            
-                for idx in (1..self.dim).rev() {
-                    if self.state[idx] == self.n {
-                        self.state[idx] = 0;
-                        self.state[idx-1] += 1;
-                    } else {
-                        break;
-                    }
-                }
-            }
+            self.step();
         }
 
         fn get(&self) -> Option<&[usize]> {
-            if self.state[0] >= self.n { None } else { Some(&self.state) }
+            self.cursor.current()
+        }
+
+        fn jump(&mut self, k: usize) {
+            self.seek( self.next_index().saturating_add(k as u128) );
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl SourceBack for Sequence {
+        fn advance_back(&mut self) {
+            self.step_back();
+        }
+    }
+
+    impl Source for Gray {
+        type Item = [usize];
+
+        fn advance(&mut self) {
+            self.step();
+        }
+
+        fn get(&self) -> Option<&[usize]> {
+            if self.ok { Some(&self.state) } else { None }
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.remaining() )
         }
     }
 }
 
 
-#[cfg(not(feature = "streaming"))]
 mod iterator {
-    use super::Sequence;
-    use std::iter::Iterator;
+    use super::{Sequence, Gray};
+    use crate::count;
+    use alloc::vec::Vec;
+    use core::iter::{Iterator, DoubleEndedIterator};
 
     impl Iterator for Sequence {
         type Item = Vec<usize>;
 
         fn next(&mut self) -> Option<Vec<usize>> {
-            if self.init {
-                self.init = false;
-            } else {
-                self.state[self.dim-1] += 1;
-            
-                for idx in (1..self.dim).rev() {
-                    if self.state[idx] == self.n {
-                        self.state[idx] = 0;
-                        self.state[idx-1] += 1;
-                    }
-                }
-            }
+            self.step();
+            self.cursor.front().map(|seq| seq.to_vec())
+        }
 
-            if self.state[0] >= self.n {
-                None
-            } else {
-                Some( self.state.clone() )
-            }
+        fn nth(&mut self, k: usize) -> Option<Vec<usize>> {
+            self.seek( self.next_index().saturating_add(k as u128) );
+            self.next()
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl DoubleEndedIterator for Sequence {
+        fn next_back(&mut self) -> Option<Vec<usize>> {
+            self.step_back();
+            self.cursor.back().map(|seq| seq.to_vec())
+        }
+    }
+
+    impl Iterator for Gray {
+        type Item = Vec<usize>;
+
+        fn next(&mut self) -> Option<Vec<usize>> {
+            self.step();
+            if self.ok { Some(self.state.clone()) } else { None }
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.remaining() )
         }
     }
 }
 
 
+#[cfg(feature = "rand")]
+mod sample {
+    use super::Sequence;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use rand::Rng;
+
+    impl Sequence {
+        /// Returns a sequence of `dim` numbers out of `0..n` chosen uniformly at random,
+        /// or `None` if `n == 0` and `dim > 0`.
+        pub fn sample<R: Rng + ?Sized>( dim: usize, n: usize, rng: &mut R ) -> Option<Vec<usize>> {
+            Sequence::sample_with_radices( &vec![ n; dim ], rng )
+        }
+
+        /// Same as `Sequence::sample`, for `Sequence::with_radices( radices )`.
+        pub fn sample_with_radices<R: Rng + ?Sized>( radices: &[usize], rng: &mut R ) -> Option<Vec<usize>> {
+            if radices.contains(&0) {
+                return None;
+            }
+            Some( radices.iter().map(|&r| rng.gen_range( 0..r )).collect() )
+        }
+    }
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -108,14 +409,33 @@ mod tests {
         let mut iter = Sequence::new( dim, n );
         let mut idx = 0;
 
+        #[allow(clippy::while_let_on_iterator)]
         while let Some(elem) = iter.next() {
             let vec = Sequence::from_index( dim, n, idx ).unwrap();
             let vec: &[usize] = &vec;
             assert_eq!( elem, vec );
+            assert_eq!( Sequence::to_index( vec, n ), Some(idx) );
             idx += 1;
         }
 
         assert_eq!( Sequence::from_index( dim, n, idx ), None );
+        assert_eq!( Sequence::to_index( &[0,4,1], n ), None );
+    }
+
+
+    #[test]
+    fn large_index() {
+        let dim = 30;
+        let n = 10;
+
+        assert_eq!( Sequence::from_index( dim, n, 123 ).unwrap()[27..], [1,2,3] );
+        assert_eq!( Sequence::to_index( &[9; 30], n ), None );
+
+        let idx = 10u128.pow(30) - 1;
+        assert_eq!( Sequence::from_index_u128( dim, n, idx ).unwrap(), vec![ 9; 30 ] );
+        assert_eq!( Sequence::to_index_u128( &[9; 30], n ), Some(idx) );
+        assert_eq!( Sequence::from_index_u128( dim, n, idx + 1 ), None );
+        assert_eq!( Sequence::to_index_u128( &[9; 40], n ), None );
     }
     
     
@@ -192,5 +512,259 @@ mod tests {
         assert_eq!( iter.next().unwrap(), &[3,3,3] );
         assert_eq!( iter.next(), None );
     }
+
+
+    #[test]
+    fn size_hint() {
+        let mut iter = Sequence::new( 3, 4 );
+        assert_eq!( iter.total(), Some(64) );
+
+        for rest in (0..64).rev() {
+            assert_eq!( iter.size_hint(), (rest+1, Some(rest+1)) );
+            iter.next().unwrap();
+            assert_eq!( iter.remaining(), Some(rest as u128) );
+        }
+
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.size_hint(), (0, Some(0)) );
+        // usize に収まらないので、正確な数は remaining() で分かる
+        let iter = Sequence::new( 30, 10 );
+        assert_eq!( iter.total(), Some(10u128.pow(30)) );
+        assert_eq!( iter.remaining(), Some(10u128.pow(30)) );
+        assert_eq!( iter.size_hint(), (usize::MAX, None) );
+    }
+
+
+    #[test]
+    fn next_back() {
+        let dim = 3;
+        let n = 3;
+
+        let mut forward = Vec::new();
+        let mut iter = Sequence::new( dim, n );
+        for elem in iter.by_ref() {
+            forward.push( elem.to_vec() );
+        }
+
+        let mut backward = Vec::new();
+        let mut iter = Sequence::new( dim, n );
+        while let Some(elem) = iter.next_back() {
+            backward.push( elem.to_vec() );
+        }
+        backward.reverse();
+        assert_eq!( backward, forward );
+
+        // 両端から交互に取り出す
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        let mut iter = Sequence::new( dim, n );
+        while let Some(elem) = iter.next() {
+            front.push( elem.to_vec() );
+            match iter.next_back() {
+                Some(elem) => back.push( elem.to_vec() ),
+                None => break,
+            }
+        }
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.next_back(), None );
+        back.reverse();
+        front.append( &mut back );
+        assert_eq!( front, forward );
+    }
+
+
+    #[test]
+    fn seek() {
+        let dim = 3;
+        let n = 3;
+
+        let mut all = Vec::new();
+        let mut iter = Sequence::new( dim, n );
+        for elem in iter.by_ref() {
+            all.push( elem.to_vec() );
+        }
+
+        for (idx, elem) in all.iter().enumerate() {
+            let mut iter = Sequence::new( dim, n );
+            iter.seek( idx as u128 );
+            assert_eq!( iter.next().unwrap(), &elem[..] );
+            assert_eq!( iter.next_index(), idx as u128 + 1 );
+        }
+
+        let mut iter = Sequence::new( dim, n );
+        assert_eq!( iter.nth(3).unwrap(), &all[3][..] );
+        assert_eq!( iter.nth(2).unwrap(), &all[6][..] );
+        assert_eq!( iter.nth(all.len()), None );
+        iter.seek( 1 );
+        assert_eq!( iter.next().unwrap(), &all[1][..] );
+
+        // 後ろから取り出したものには飛ばない
+        let mut iter = Sequence::new( dim, n );
+        iter.next_back();
+        iter.seek( all.len() as u128 - 1 );
+        assert_eq!( iter.next(), None );
+
+        // 添字が usize に収まらない場合
+        let mut iter = Sequence::new( 30, 10 );
+        let idx = iter.total().unwrap() / 3;
+        iter.seek( idx );
+        let elem = iter.next().unwrap().to_vec();
+        let next = iter.next().unwrap().to_vec();
+        iter.seek( idx + 1 );
+        assert_eq!( iter.next().unwrap(), &next[..] );
+        assert_ne!( elem, next );
+    }
+
+
+    #[test]
+    fn edge_cases() {
+        // 空の列がちょうど 1 つ
+        for n in 0..3 {
+            let mut iter = Sequence::new( 0, n );
+            assert_eq!( iter.total(), Some(1) );
+            assert_eq!( iter.next().unwrap(), &[] );
+            assert_eq!( iter.next(), None );
+            assert_eq!( iter.next_back(), None );
+
+            let mut iter = Sequence::new( 0, n );
+            assert_eq!( iter.next_back().unwrap(), &[] );
+            assert_eq!( iter.next(), None );
+            assert!( Sequence::try_new( 0, n ).is_ok() );
+        }
+
+        let mut iter = Sequence::new( 4, 0 );
+        assert_eq!( iter.total(), Some(0) );
+        assert_eq!( iter.next(), None );
+        assert_eq!( iter.next_back(), None );
+        assert_eq!( Sequence::try_new( 4, 0 ).unwrap_err(), Error::EmptyRange { dim: 4 } );
+    }
+
+
+    #[test]
+    fn gray() {
+        let mut iter = sequence::Gray::new( 2, 3 );
+        let mut all = Vec::new();
+        while let Some(seq) = iter.next() {
+            all.push(( seq.to_vec(), iter.change() ));
+        }
+        assert_eq!( all, vec![
+            (vec![0,0], None),
+            (vec![0,1], Some((1,1))),
+            (vec![0,2], Some((1,1))),
+            (vec![1,2], Some((0,1))),
+            (vec![1,1], Some((1,-1))),
+            (vec![1,0], Some((1,-1))),
+            (vec![2,0], Some((0,1))),
+            (vec![2,1], Some((1,1))),
+            (vec![2,2], Some((1,1))),
+        ]);
+
+        let iter = sequence::Gray::new( 30, 10 );
+        assert_eq!( iter.remaining(), Some(10u128.pow(30)) );
+        assert_eq!( iter.size_hint(), (usize::MAX, None) );
+
+        for n in 0..5 {
+            for dim in 0..4 {
+                let mut iter = sequence::Gray::new( dim, n );
+                let mut all: Vec<Vec<usize>> = Vec::new();
+                while let Some(seq) = iter.next() {
+                    let seq = seq.to_vec();
+                    if let Some((i, d)) = iter.change() {
+                        let mut prev = all.last().unwrap().clone();
+                        prev[i] = (prev[i] as isize + d) as usize;
+                        assert_eq!( prev, seq );
+                    }
+                    all.push(seq);
+                }
+                assert_eq!( iter.remaining(), Some(0) );
+                all.sort();
+                let mut expected = Vec::new();
+                let mut iter = Sequence::new( dim, n );
+                for seq in iter.by_ref() {
+                    expected.push(seq.to_vec());
+                }
+                assert_eq!( all, expected );
+            }
+        }
+    }
+
+
+    #[test]
+    fn with_radices() {
+        let radices = [2, 1, 3];
+        let mut iter = Sequence::with_radices( &radices );
+        assert_eq!( iter.size_hint(), (6, Some(6)) );
+        assert_eq!( iter.next().unwrap(), &[0,0,0] );
+        assert_eq!( iter.next().unwrap(), &[0,0,1] );
+        assert_eq!( iter.next().unwrap(), &[0,0,2] );
+        assert_eq!( iter.next().unwrap(), &[1,0,0] );
+        assert_eq!( iter.next_back().unwrap(), &[1,0,2] );
+        assert_eq!( iter.next().unwrap(), &[1,0,1] );
+        assert_eq!( iter.next(), None );
+
+        let mut iter = Sequence::with_radices( &radices );
+        let mut idx = 0;
+        for seq in iter.by_ref() {
+            let vec = Sequence::from_index_with_radices( &radices, idx ).unwrap();
+            let vec: &[usize] = &vec;
+            assert_eq!( seq, vec );
+            assert_eq!( Sequence::to_index_with_radices( vec, &radices ), Some(idx) );
+            idx += 1;
+        }
+        assert_eq!( Sequence::from_index_with_radices( &radices, idx ), None );
+        assert_eq!( Sequence::to_index_with_radices( &[0,1,0], &radices ), None );
+        assert_eq!( Sequence::to_index_with_radices( &[0,0], &radices ), None );
+
+        let mut iter = Sequence::with_radices( &[3, 0] );
+        assert_eq!( iter.next(), None );
+
+        let mut iter = sequence::Gray::with_radices( &[2, 1, 3] );
+        let mut all = Vec::new();
+        for seq in iter.by_ref() {
+            all.push(seq.to_vec());
+        }
+        assert_eq!( all, vec![
+            vec![0,0,0], vec![0,0,1], vec![0,0,2], vec![1,0,2], vec![1,0,1], vec![1,0,0],
+        ]);
+    }
+
+
+    #[test]
+    fn for_each_slice() {
+        use std::ops::ControlFlow;
+
+        let mut iter = Sequence::new( 3, 3 );
+        let mut expected = Sequence::new( 3, 3 );
+        let flow = iter.for_each_slice(|seq| {
+            assert_eq!( seq, &expected.next().unwrap()[..] );
+            if seq == [1,1,1] { ControlFlow::Break(seq.to_vec()) } else { ControlFlow::Continue(()) }
+        });
+        assert_eq!( flow, ControlFlow::Break(vec![1,1,1]) );
+        assert_eq!( iter.next_index(), 14 );
+
+        let mut sum = 0;
+        let _: ControlFlow<()> = sequence::Gray::new( 3, 3 ).for_each_slice(|seq| {
+            sum += seq.iter().sum::<usize>();
+            ControlFlow::Continue(())
+        });
+        assert_eq!( sum, 81 );
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn sample() {
+        use rand::{SeedableRng, rngs::SmallRng};
+
+        let mut rng = SmallRng::seed_from_u64( 1 );
+        let mut hist = [0; 12];
+        for _ in 0..12000 {
+            let seq = Sequence::sample_with_radices( &[2, 3, 2], &mut rng ).unwrap();
+            hist[ Sequence::to_index_with_radices( &seq, &[2, 3, 2] ).unwrap() as usize ] += 1;
+        }
+        assert!( hist.iter().all(|&c| 800 < c && c < 1200), "{:?}", hist );
+
+        assert_eq!( Sequence::sample( 2, 1, &mut rng ), Some(vec![0, 0]) );
+        assert_eq!( Sequence::sample( 0, 0, &mut rng ), Some(vec![]) );
+        assert_eq!( Sequence::sample( 1, 0, &mut rng ), None );
+    }
 }
-    
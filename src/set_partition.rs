@@ -0,0 +1,625 @@
+//! Partitions of a set `0..n` into non-empty blocks.
+//!
+//! A partition is represented by its restricted growth string: the `i`-th number tells which
+//! block `i` belongs to, where the blocks are numbered in the order of their smallest elements.
+//! So it starts with 0, and each number is at most one more than the maximum before it.
+//! `blocks()` turns it into the list of blocks.
+//!
+//! ```
+//! use enumcombinatorics::*;
+//!
+//! let mut iter = SetPartition::new( 3 );
+//!
+//! assert_eq!( iter.next().unwrap(), &[0,0,0] );
+//! assert_eq!( iter.next().unwrap(), &[0,0,1] );
+//! assert_eq!( iter.next().unwrap(), &[0,1,0] );
+//! assert_eq!( iter.next().unwrap(), &[0,1,1] );
+//! assert_eq!( iter.next().unwrap(), &[0,1,2] );
+//! assert_eq!( iter.next(), None );
+//!
+//! let mut iter = SetPartition::with_blocks( 3, 2 ).blocks();
+//!
+//! assert_eq!( iter.next().unwrap(), vec![ vec![0,1], vec![2] ] );
+//! assert_eq!( iter.next().unwrap(), vec![ vec![0,2], vec![1] ] );
+//! assert_eq!( iter.next().unwrap(), vec![ vec![0], vec![1,2] ] );
+//! assert_eq!( iter.next(), None );
+//! ```
+
+
+use crate::{count, Streaming};
+use crate::cursor::Cursor;
+use core::convert::TryFrom;
+use core::ops::ControlFlow;
+use alloc::vec;
+use alloc::vec::Vec;
+
+
+#[derive(Debug, Clone)]
+pub struct SetPartition {
+    cursor: Cursor,
+    n: usize,
+    /// Number of blocks, or `None` for any number.
+    k: Option<usize>,
+}
+
+impl SetPartition {
+    /// Generates all partitions of `0..n` in lexicographic order of the restricted growth strings.
+    ///
+    /// There is exactly one (empty) partition if `n == 0`.
+    pub fn new( n: usize ) -> Self {
+        SetPartition::with_k( n, None )
+    }
+
+    /// Same as `SetPartition::new`, but generates only the partitions into exactly `k` blocks.
+    ///
+    /// There are none if `k > n`, or if `k == 0` and `n > 0`.
+    pub fn with_blocks( n: usize, k: usize ) -> Self {
+        SetPartition::with_k( n, Some(k) )
+    }
+
+    fn with_k( n: usize, k: Option<usize> ) -> Self {
+        SetPartition { cursor: Cursor::new( first( n, k ), n, total( n, k ) ), n, k }
+    }
+
+    /// Number of blocks of the partitions, or `None` if it is not fixed.
+    pub fn num_blocks(&self) -> Option<usize> {
+        self.k
+    }
+
+    /// Number of all partitions, or `None` if it does not fit in `u128`.
+    pub fn total(&self) -> Option<u128> {
+        total( self.n, self.k )
+    }
+
+    /// Number of partitions not yet generated, or `None` if it does not fit in `u128`.
+    pub fn remaining(&self) -> Option<u128> {
+        self.cursor.remaining()
+    }
+
+    /// Returns the `idx`-th partition (counting from 0) in the order of the iterator,
+    /// or `None` if there are not so many partitions.
+    pub fn from_index( n: usize, idx: usize ) -> Option<Vec<usize>> {
+        unrank( n, None, idx as u128 )
+    }
+
+    /// Returns the position of `rgs` in the order of the iterator, that is, the inverse of
+    /// `SetPartition::from_index`.
+    ///
+    /// Returns `None` if `rgs` is not a restricted growth string or the index does not fit in `usize`.
+    pub fn to_index( rgs: &[usize] ) -> Option<usize> {
+        usize::try_from( rank( rgs, None )? ).ok()
+    }
+
+    /// Same as `SetPartition::from_index`, for `SetPartition::with_blocks( n, k )`.
+    pub fn from_index_with_blocks( n: usize, k: usize, idx: u128 ) -> Option<Vec<usize>> {
+        unrank( n, Some(k), idx )
+    }
+
+    /// Same as `SetPartition::to_index`, for `SetPartition::with_blocks( rgs.len(), k )`.
+    ///
+    /// Returns `None` also if `rgs` does not have exactly `k` blocks.
+    pub fn to_index_with_blocks( rgs: &[usize], k: usize ) -> Option<u128> {
+        rank( rgs, Some(k) )
+    }
+
+    /// Moves the iterator so that the next call of `next()` returns the `idx`-th partition,
+    /// without generating the ones in between.
+    pub fn seek( &mut self, idx: u128 ) {
+        let (n, k) = (self.n, self.k);
+        self.cursor.seek( idx, || unrank( n, k, idx ) );
+    }
+
+    /// Position of the partition which the next call of `next()` returns.
+    pub fn next_index(&self) -> u128 {
+        self.cursor.next_index()
+    }
+
+    /// Calls `f` on each remaining partition by reference without allocating, until `f` returns
+    /// `ControlFlow::Break`. The iterator can be resumed after it.
+    pub fn for_each_slice<B, F>( &mut self, f: F ) -> ControlFlow<B>
+        where F: FnMut(&[usize]) -> ControlFlow<B>
+    {
+        let k = self.k;
+        self.cursor.for_each(|state| next_state( state, k ), f)
+    }
+
+    /// Borrowed view of the iterator, which does not allocate on each step.
+    pub fn streaming(self) -> Streaming<Self> {
+        Streaming::new( self )
+    }
+
+    /// Iterator generating the same partitions as lists of blocks.
+    pub fn blocks(self) -> Blocks {
+        Blocks { inner: self }
+    }
+
+    fn step(&mut self) {
+        let k = self.k;
+        self.cursor.step(|state| next_state( state, k ));
+    }
+
+    fn step_back(&mut self) {
+        let (n, k) = (self.n, self.k);
+        self.cursor.step_back(|| last( n, k ), |state| prev_state( state, k ));
+    }
+}
+
+
+/// Converts a restricted growth string into the list of blocks, each of which is increasing.
+///
+/// Returns `None` if `rgs` is not a restricted growth string.
+pub fn to_blocks( rgs: &[usize] ) -> Option<Vec<Vec<usize>>> {
+    let mut blocks: Vec<Vec<usize>> = Vec::new();
+
+    for (i, &x) in rgs.iter().enumerate() {
+        if x == blocks.len() {
+            blocks.push( Vec::new() );
+        } else if x > blocks.len() {
+            return None;
+        }
+        blocks[x].push(i);
+    }
+
+    Some(blocks)
+}
+
+/// Converts a list of blocks into the restricted growth string, that is, the inverse of `to_blocks`.
+///
+/// The blocks may be in any order. Returns `None` if they are not non-empty and disjoint,
+/// or do not cover `0..n` for some `n`.
+pub fn from_blocks( blocks: &[Vec<usize>] ) -> Option<Vec<usize>> {
+    let n = blocks.iter().map(|block| block.len()).sum();
+    let mut block_of = vec![ None; n ];
+
+    for (b, block) in blocks.iter().enumerate() {
+        if block.is_empty() {
+            return None;
+        }
+        for &x in block.iter() {
+            match block_of.get_mut(x) {
+                Some(slot @ None) => *slot = Some(b),
+                _ => return None,
+            }
+        }
+    }
+
+    // 最小の要素の順に番号を付け直す
+    let mut number = vec![ None; blocks.len() ];
+    let mut len = 0;
+    let rgs = block_of.into_iter().map(|b| {
+        let b = b.unwrap();
+        *number[b].get_or_insert_with(|| { len += 1; len - 1 })
+    }).collect();
+
+    Some(rgs)
+}
+
+
+/// Same as `SetPartition::total`.
+fn total( n: usize, k: Option<usize> ) -> Option<u128> {
+    match k {
+        Some(k) => count::stirling2_u128( n, k ),
+        None => count::bell_u128( n ),
+    }
+}
+
+/// The first partition in lexicographic order, which puts all but the last `k-1` elements in one block.
+fn first( n: usize, k: Option<usize> ) -> Vec<usize> {
+    let k = k.unwrap_or(1);
+    // k > n なら中身は使われない
+    (0..n).map(|i| k.saturating_sub(n - i)).collect()
+}
+
+/// The last partition in lexicographic order, which puts the first `k-1` elements in their own blocks.
+fn last( n: usize, k: Option<usize> ) -> Vec<usize> {
+    let max = k.unwrap_or(n).saturating_sub(1);
+    (0..n).map(|i| i.min(max)).collect()
+}
+
+/// Rearranges `state` into the next restricted growth string with `k` blocks (if specified),
+/// or returns `false` if it is the last one.
+fn next_state( state: &mut [usize], k: Option<usize> ) -> bool {
+    let len = state.len();
+
+    // 増やせる一番右の位置を探す。そこで使われているブロック数が k を超えてはいけない
+    let mut max = 0;
+    let mut pivot = None;
+    for (i, &x) in state.iter().enumerate().skip(1) {
+        if x <= max && k.map_or(true, |k| max.max(x + 1) < k) {
+            pivot = Some(i);
+        }
+        max = max.max(x);
+    }
+
+    let i = match pivot {
+        Some(i) => i,
+        None => return false,
+    };
+    state[i] += 1;
+
+    // 残りは 0 で埋め、足りないブロックを末尾に 1 つずつ置く
+    let used = state[..i+1].iter().max().unwrap() + 1;
+    let k = k.unwrap_or(used);
+    for (j, x) in state.iter_mut().enumerate().skip(i+1) {
+        *x = if len - j <= k - used { k - (len - j) } else { 0 };
+    }
+    true
+}
+
+/// Rearranges `state` into the previous restricted growth string with `k` blocks (if specified),
+/// or returns `false` if it is the first one.
+fn prev_state( state: &mut [usize], k: Option<usize> ) -> bool {
+    let len = state.len();
+
+    // 減らした後、残りで k 個のブロックに届く一番右の位置を探す
+    let mut max = 0;
+    let mut pivot = None;
+    for (i, &x) in state.iter().enumerate().skip(1) {
+        if x > 0 && k.map_or(true, |k| max.max(x - 1) + len - i >= k) {
+            pivot = Some(i);
+        }
+        max = max.max(x);
+    }
+
+    let i = match pivot {
+        Some(i) => i,
+        None => return false,
+    };
+    state[i] -= 1;
+
+    // 残りはできるだけ新しいブロックを開く
+    let used = state[..i+1].iter().max().unwrap() + 1;
+    let max = k.unwrap_or(len).saturating_sub(1);
+    for (j, x) in state.iter_mut().enumerate().skip(i+1) {
+        *x = (used + j - i - 1).min(max);
+    }
+    true
+}
+
+/// `table[r][m]` is the number of ways to extend a restricted growth string using `m` blocks
+/// by `r` numbers so that it has `k` blocks (if specified), or `None` if it does not fit in `u128`.
+///
+/// Only `m <= n - r` is filled, which is all a string of length `n` needs.
+fn completions( n: usize, k: Option<usize> ) -> Vec<Vec<Option<u128>>> {
+    let mut table: Vec<Vec<Option<u128>>> = Vec::with_capacity(n);
+    table.push( (0..n+1).map(|m| Some(if k.map_or(true, |k| m == k) { 1 } else { 0 })).collect() );
+
+    for r in 1..n {
+        let prev = &table[r-1];
+        // 既存のブロックのどれかに入れるか、新しいブロックを開く
+        let row = (0..n-r+1).map(|m| {
+            if k.map_or(false, |k| m > k) {
+                return Some(0);
+            }
+            prev[m]?.checked_mul(m as u128)?.checked_add(prev[m+1]?)
+        }).collect();
+        table.push(row);
+    }
+
+    table
+}
+
+/// Same as `SetPartition::from_index_with_blocks`, or `SetPartition::from_index` if `k` is `None`.
+fn unrank( n: usize, k: Option<usize>, idx: u128 ) -> Option<Vec<usize>> {
+    if n == 0 {
+        return if idx == 0 && k.unwrap_or(0) == 0 { Some(Vec::new()) } else { None };
+    }
+
+    let table = completions( n, k );
+    // None はあふれたもので、どの idx よりも大きい
+    if table[n-1][1].map_or(false, |total| idx >= total) {
+        return None;
+    }
+
+    let mut rgs = vec![ 0; n ];
+    let mut idx = idx;
+    let mut used = 1;
+    for (i, slot) in rgs.iter_mut().enumerate().skip(1) {
+        let r = n - 1 - i;
+        // 既存のブロック 0..used のどれに入れても、残りの埋め方は同じ数だけある
+        let x = match table[r][used] {
+            Some(0) => used,
+            Some(c) => (idx / c).min(used as u128) as usize,
+            None => 0,
+        };
+        if x > 0 {
+            idx -= table[r][used].unwrap() * x as u128;
+        }
+        if x == used {
+            used += 1;
+        }
+        *slot = x;
+    }
+
+    Some(rgs)
+}
+
+/// Same as `SetPartition::to_index_with_blocks`, or `SetPartition::to_index` if `k` is `None`.
+fn rank( rgs: &[usize], k: Option<usize> ) -> Option<u128> {
+    let n = rgs.len();
+    if n == 0 {
+        return if k.unwrap_or(0) == 0 { Some(0) } else { None };
+    }
+    if rgs[0] != 0 {
+        return None;
+    }
+
+    let table = completions( n, k );
+    let mut idx: u128 = 0;
+    let mut used = 1;
+    for (i, &x) in rgs.iter().enumerate().skip(1) {
+        if x > used {
+            return None;
+        }
+        if x > 0 {
+            let c = table[n-1-i][used]?;
+            idx = idx.checked_add(c.checked_mul(x as u128)?)?;
+        }
+        if x == used {
+            used += 1;
+        }
+    }
+
+    if k.map_or(true, |k| used == k) { Some(idx) } else { None }
+}
+
+
+/// The partitions of `SetPartition` as lists of blocks, created by `SetPartition::blocks`.
+#[derive(Debug, Clone)]
+pub struct Blocks {
+    inner: SetPartition,
+}
+
+impl Blocks {
+    /// The underlying iterator of restricted growth strings.
+    pub fn inner(&self) -> &SetPartition {
+        &self.inner
+    }
+
+    /// The underlying iterator of restricted growth strings, e.g. to `seek` it.
+    pub fn inner_mut(&mut self) -> &mut SetPartition {
+        &mut self.inner
+    }
+}
+
+
+mod streaming {
+    use super::SetPartition;
+    use crate::count;
+    use crate::streaming::{Source, SourceBack};
+
+    impl Source for SetPartition {
+        type Item = [usize];
+
+        fn advance(&mut self) {
+            self.step();
+        }
+
+        fn get(&self) -> Option<&[usize]> {
+            self.cursor.current()
+        }
+
+        fn jump(&mut self, k: usize) {
+            self.seek( self.next_index().saturating_add(k as u128) );
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl SourceBack for SetPartition {
+        fn advance_back(&mut self) {
+            self.step_back();
+        }
+    }
+}
+
+
+mod iterator {
+    use super::{SetPartition, Blocks, to_blocks};
+    use crate::count;
+    use alloc::vec::Vec;
+    use core::iter::{Iterator, DoubleEndedIterator};
+
+    impl Iterator for SetPartition {
+        type Item = Vec<usize>;
+
+        fn next(&mut self) -> Option<Vec<usize>> {
+            self.step();
+            self.cursor.front().map(|rgs| rgs.to_vec())
+        }
+
+        fn nth(&mut self, k: usize) -> Option<Vec<usize>> {
+            self.seek( self.next_index().saturating_add(k as u128) );
+            self.next()
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            count::size_hint( self.cursor.remaining() )
+        }
+    }
+
+    impl DoubleEndedIterator for SetPartition {
+        fn next_back(&mut self) -> Option<Vec<usize>> {
+            self.step_back();
+            self.cursor.back().map(|rgs| rgs.to_vec())
+        }
+    }
+
+    impl Iterator for Blocks {
+        type Item = Vec<Vec<usize>>;
+
+        fn next(&mut self) -> Option<Vec<Vec<usize>>> {
+            self.inner.step();
+            self.inner.cursor.front().and_then(to_blocks)
+        }
+
+        fn nth(&mut self, k: usize) -> Option<Vec<Vec<usize>>> {
+            self.inner.seek( self.inner.next_index().saturating_add(k as u128) );
+            self.next()
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            self.inner.size_hint()
+        }
+    }
+
+    impl DoubleEndedIterator for Blocks {
+        fn next_back(&mut self) -> Option<Vec<Vec<usize>>> {
+            self.inner.step_back();
+            self.inner.cursor.back().and_then(to_blocks)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use crate::set_partition::{to_blocks, from_blocks};
+
+    /// Number of blocks of a restricted growth string.
+    fn num_blocks( rgs: &[usize] ) -> usize {
+        rgs.iter().max().map_or(0, |&m| m + 1)
+    }
+
+    #[test]
+    fn detail() {
+        let all: Vec<Vec<usize>> = SetPartition::new( 5 ).collect();
+        assert_eq!( all.len(), 52 );
+        assert!( all.windows(2).all(|w| w[0] < w[1]) );
+        for rgs in all.iter() {
+            assert_eq!( rgs[0], 0 );
+            assert!( (1..5).all(|i| rgs[i] <= num_blocks( &rgs[..i] )) );
+        }
+
+        for k in 0..7 {
+            let expected: Vec<Vec<usize>> = all.iter().filter(|rgs| num_blocks( rgs ) == k).cloned().collect();
+            let iter = SetPartition::with_blocks( 5, k );
+            assert_eq!( iter.size_hint(), (expected.len(), Some(expected.len())) );
+            assert_eq!( iter.collect::<Vec<_>>(), expected );
+        }
+
+        let iter = SetPartition::new( 30 );
+        assert_eq!( iter.remaining(), count::bell_u128( 30 ) );
+        assert_eq!( iter.size_hint(), (usize::MAX, None) );
+        assert_eq!( iter.blocks().size_hint(), (usize::MAX, None) );
+
+        assert_eq!( SetPartition::new( 0 ).collect::<Vec<_>>(), vec![ vec![] ] );
+        assert_eq!( SetPartition::with_blocks( 0, 0 ).collect::<Vec<_>>(), vec![ vec![] ] );
+        assert_eq!( SetPartition::with_blocks( 0, 1 ).next(), None );
+        assert_eq!( SetPartition::new( 1 ).collect::<Vec<_>>(), vec![ vec![0] ] );
+    }
+
+
+    #[test]
+    fn from_index() {
+        for (k, iter) in [ (None, SetPartition::new( 6 )), (Some(3), SetPartition::with_blocks( 6, 3 )) ] {
+            let total = iter.total().unwrap();
+            for (idx, rgs) in iter.enumerate() {
+                match k {
+                    Some(k) => {
+                        assert_eq!( SetPartition::from_index_with_blocks( 6, k, idx as u128 ).unwrap(), rgs );
+                        assert_eq!( SetPartition::to_index_with_blocks( &rgs, k ), Some(idx as u128) );
+                    },
+                    None => {
+                        assert_eq!( SetPartition::from_index( 6, idx ).unwrap(), rgs );
+                        assert_eq!( SetPartition::to_index( &rgs ), Some(idx) );
+                    },
+                }
+            }
+            assert_eq!( SetPartition::from_index_with_blocks( 6, k.unwrap_or(0), total ), None );
+        }
+
+        assert_eq!( SetPartition::from_index( 6, 203 ), None );
+        assert_eq!( SetPartition::to_index( &[1,0] ), None );
+        assert_eq!( SetPartition::to_index( &[0,2] ), None );
+        assert_eq!( SetPartition::to_index_with_blocks( &[0,1,1], 3 ), None );
+
+        // 総数が u128 に収まらなくても、先頭の方は求まる
+        let rgs = SetPartition::from_index( 200, 1 ).unwrap();
+        assert_eq!( rgs[199], 1 );
+        assert_eq!( SetPartition::to_index( &rgs ), Some(1) );
+        let last: Vec<usize> = (0..200).collect();
+        assert_eq!( SetPartition::to_index( &last ), None );
+        assert_eq!( SetPartition::to_index_with_blocks( &last, 200 ), Some(0) );
+    }
+
+
+    #[test]
+    fn next_back() {
+        for iter in [ SetPartition::new( 5 ), SetPartition::with_blocks( 6, 2 ), SetPartition::with_blocks( 6, 5 ) ] {
+            let forward: Vec<Vec<usize>> = iter.clone().collect();
+            let mut backward: Vec<Vec<usize>> = iter.clone().rev().collect();
+            backward.reverse();
+            assert_eq!( forward, backward );
+
+            // 両端から交互に取り出す
+            let mut iter = iter;
+            let (mut front, mut back) = (Vec::new(), Vec::new());
+            while let Some(x) = iter.next() {
+                front.push(x);
+                if let Some(y) = iter.next_back() {
+                    back.push(y);
+                }
+            }
+            back.reverse();
+            front.extend(back);
+            assert_eq!( front, forward );
+        }
+
+        // Bell(40) は u128 に収まるが usize には収まらない
+        let mut iter = SetPartition::new( 40 );
+        let last = iter.clone().next_back().unwrap();
+        assert_eq!( last, (0..40).collect::<Vec<_>>() );
+        iter.seek( iter.total().unwrap() - 1 );
+        assert_eq!( iter.next().unwrap(), last );
+        assert_eq!( iter.next(), None );
+    }
+
+
+    #[test]
+    fn seek() {
+        let all: Vec<Vec<usize>> = SetPartition::with_blocks( 6, 3 ).collect();
+        let mut iter = SetPartition::with_blocks( 6, 3 );
+        iter.seek( 40 );
+        assert_eq!( iter.next_index(), 40 );
+        assert_eq!( iter.next().unwrap(), all[40] );
+        assert_eq!( iter.nth( 10 ).unwrap(), all[51] );
+
+        let mut stream = SetPartition::with_blocks( 6, 3 ).streaming();
+        assert_eq!( stream.nth( 3 ).unwrap(), &all[3][..] );
+        assert_eq!( stream.next_back().unwrap(), &all[89][..] );
+
+        let mut count = 0;
+        let _ = SetPartition::with_blocks( 6, 3 ).for_each_slice(|rgs| {
+            assert_eq!( rgs, &all[count][..] );
+            count += 1;
+            core::ops::ControlFlow::<()>::Continue(())
+        });
+        assert_eq!( count, 90 );
+    }
+
+
+    #[test]
+    fn blocks() {
+        let mut iter = SetPartition::new( 4 ).blocks();
+        assert_eq!( iter.size_hint(), (15, Some(15)) );
+        assert_eq!( iter.next().unwrap(), vec![ vec![0,1,2,3] ] );
+        assert_eq!( iter.next_back().unwrap(), vec![ vec![0], vec![1], vec![2], vec![3] ] );
+        assert_eq!( iter.nth( 1 ).unwrap(), vec![ vec![0,1,3], vec![2] ] );
+
+        for rgs in SetPartition::new( 5 ) {
+            let blocks = to_blocks( &rgs ).unwrap();
+            assert_eq!( blocks.len(), num_blocks( &rgs ) );
+            assert_eq!( from_blocks( &blocks ).unwrap(), rgs );
+        }
+
+        assert_eq!( from_blocks( &[ vec![3,1], vec![2], vec![0] ] ), Some(vec![0,1,2,1]) );
+        assert_eq!( from_blocks( &[ vec![0,1], vec![] ] ), None );
+        assert_eq!( from_blocks( &[ vec![0,1], vec![1] ] ), None );
+        assert_eq!( from_blocks( &[ vec![0,2] ] ), None );
+        assert_eq!( to_blocks( &[0,2] ), None );
+    }
+}
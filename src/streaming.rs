@@ -0,0 +1,200 @@
+//! Borrowed views of the iterators, which do not allocate on each step.
+//!
+//! `Iterator::next()` has to return an owned `Vec<usize>`, so it clones the internal state
+//! every time. `streaming()` turns an iterator into `Streaming`, whose `next()` returns
+//! a reference to the internal state instead.
+//!
+//! ```
+//! use enumcombinatorics::*;
+//!
+//! let mut iter = Combination::new( 2, 3 ).streaming();
+//!
+//! assert_eq!( iter.next().unwrap(), &[0,1] );
+//! assert_eq!( iter.next_back().unwrap(), &[1,2] );
+//! assert_eq!( iter.next().unwrap(), &[0,2] );
+//! assert_eq!( iter.next(), None );
+//! ```
+//!
+//! With the `streaming` feature, `Streaming` also implements `StreamingIterator`
+//! (and `DoubleEndedStreamingIterator` if possible) of the `streaming_iterator` crate.
+
+
+/// A borrowed view of an iterator, created by `streaming()` of each iterator.
+#[derive(Debug, Clone)]
+pub struct Streaming<I> {
+    inner: I,
+}
+
+impl<I> Streaming<I> {
+    pub(crate) fn new( inner: I ) -> Self {
+        Streaming { inner }
+    }
+
+    /// The underlying iterator.
+    pub fn inner(&self) -> &I {
+        &self.inner
+    }
+
+    /// The underlying iterator, e.g. to `seek` it.
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.inner
+    }
+
+    /// Returns the underlying iterator.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+#[allow(clippy::should_implement_trait)]
+impl<I: Source> Streaming<I> {
+    /// Advances the iterator and returns the next item.
+    pub fn next(&mut self) -> Option<&I::Item> {
+        self.inner.advance();
+        self.inner.get()
+    }
+
+    /// Returns the item last returned by `next()` or `next_back()`.
+    pub fn get(&self) -> Option<&I::Item> {
+        self.inner.get()
+    }
+
+    /// Skips `k` items and returns the next one.
+    pub fn nth( &mut self, k: usize ) -> Option<&I::Item> {
+        self.inner.jump(k);
+        self.next()
+    }
+
+    /// Same as `Iterator::size_hint` of the underlying iterator.
+    pub fn size_hint(&self) -> (usize,Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<I: SourceBack> Streaming<I> {
+    /// Advances the iterator from the back and returns the previous item.
+    pub fn next_back(&mut self) -> Option<&I::Item> {
+        self.inner.advance_back();
+        self.inner.get()
+    }
+}
+
+
+mod source {
+    /// What `Streaming` needs from an iterator.
+    ///
+    /// This is public only nominally so that it can appear in the bounds of `Streaming`.
+    pub trait Source {
+        type Item: ?Sized;
+
+        /// Moves to the next item.
+        fn advance(&mut self);
+
+        /// The item last reached from either end.
+        fn get(&self) -> Option<&Self::Item>;
+
+        fn size_hint(&self) -> (usize,Option<usize>);
+
+        /// Skips `k` items, so that the next `advance()` reaches the `k`-th one from now.
+        fn jump( &mut self, k: usize ) {
+            for _ in 0..k {
+                self.advance();
+            }
+        }
+    }
+
+    /// `Source` which can also move backward.
+    pub trait SourceBack: Source {
+        /// Moves to the previous item from the back.
+        fn advance_back(&mut self);
+    }
+}
+
+pub(crate) use self::source::{Source, SourceBack};
+
+
+#[cfg(feature = "streaming")]
+mod streaming_iterator {
+    use super::{Streaming, Source, SourceBack};
+    use streaming_iterator::{StreamingIterator, DoubleEndedStreamingIterator};
+
+    impl<I: Source> StreamingIterator for Streaming<I> {
+        type Item = I::Item;
+
+        fn advance(&mut self) {
+            self.inner.advance();
+        }
+
+        fn get(&self) -> Option<&I::Item> {
+            self.inner.get()
+        }
+
+        fn nth(&mut self, k: usize) -> Option<&I::Item> {
+            self.inner.jump(k);
+            StreamingIterator::next(self)
+        }
+
+        fn size_hint(&self) -> (usize,Option<usize>) {
+            self.inner.size_hint()
+        }
+    }
+
+    impl<I: SourceBack> DoubleEndedStreamingIterator for Streaming<I> {
+        fn advance_back(&mut self) {
+            self.inner.advance_back();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn same_as_iterator() {
+        let mut iter = Permutation::new( 3, 5 );
+        let mut stream = Permutation::new( 3, 5 ).streaming();
+        assert_eq!( stream.size_hint(), (60, Some(60)) );
+        for _ in 0..20 {
+            assert_eq!( stream.next().unwrap(), &iter.next().unwrap()[..] );
+            let back = iter.next_back().unwrap();
+            assert_eq!( stream.next_back().unwrap(), &back[..] );
+            assert_eq!( stream.get().unwrap(), &back[..] );
+        }
+        assert_eq!( stream.nth( 5 ).unwrap(), &iter.nth( 5 ).unwrap()[..] );
+        for seq in iter {
+            assert_eq!( stream.next().unwrap(), &seq[..] );
+        }
+        assert_eq!( stream.next(), None );
+
+        let mut iter = sequence::Gray::new( 3, 3 );
+        let mut stream = sequence::Gray::new( 3, 3 ).streaming();
+        assert_eq!( stream.nth( 4 ).unwrap(), &iter.nth( 4 ).unwrap()[..] );
+        for seq in iter {
+            assert_eq!( stream.next().unwrap(), &seq[..] );
+            assert!( stream.inner().change().is_some() );
+        }
+        assert_eq!( stream.next(), None );
+
+        let items = ["a", "b", "c"];
+        let mut stream = items.multichooses( 2 ).streaming();
+        assert_eq!( stream.next().unwrap(), &[&"a", &"a"] );
+        assert_eq!( stream.next_back().unwrap(), &[&"c", &"c"] );
+        stream.inner_mut().indices_mut().seek( 4 );
+        assert_eq!( stream.next().unwrap(), &[&"b", &"c"] );
+        assert_eq!( stream.next(), None );
+    }
+
+
+    #[cfg(feature = "streaming")]
+    #[test]
+    fn streaming_iterator() {
+        let mut stream = Combination::new( 2, 4 ).streaming();
+        assert_eq!( StreamingIterator::next( &mut stream ).unwrap(), &[0,1] );
+        assert_eq!( stream.count(), 5 );
+
+        let mut rev = Combination::new( 2, 4 ).streaming().rev();
+        assert_eq!( rev.next().unwrap(), &[2,3] );
+    }
+}
@@ -0,0 +1,192 @@
+//! Rearranging a state into the next or previous one in place, shared by the `Vec`-based
+//! iterators and the array-based ones in `array`.
+
+
+pub(crate) mod permutation {
+    /// Rearranges `state` into the next permutation in the order given by `less`.
+    /// Returns `false` if `state` is already the last one.
+    ///
+    /// `state[..dim]` is the current permutation and `state[dim..]` holds the unused numbers
+    /// sorted by `less`. This is the usual `next_permutation` applied to `state[..dim]` followed by
+    /// the unused numbers in reverse order, but the unused numbers are kept sorted so that
+    /// the common case only touches the last position.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn shift<F>( state: &mut [usize], dim: usize, less: F ) -> bool
+        where F: Fn(usize, usize) -> bool
+    {
+        let n = state.len();
+
+        if dim == 0 {
+            return false;
+        }
+
+        // 最後の数をより大きい未使用の数に取り替える
+        if dim < n && less(state[dim-1], state[n-1]) {
+            let last = state[dim-1];
+            let j = dim + state[dim..].partition_point(|&x| less(x, last));
+            state.swap(dim-1, j);
+            return true;
+        }
+
+        // ここでは未使用の数はすべて state[dim-1] より小さい
+        let i = match (0..dim-1).rev().find(|&i| less(state[i], state[i+1])) {
+            Some(i) => i,
+            None => return false,
+        };
+
+        // state[i] より大きい数のうち最小のものと交換する
+        let pivot = state[i];
+        let j = match dim + state[dim..].partition_point(|&x| less(x, pivot)) {
+            j if j < n => j,
+            _ => (i+1..dim).rev().find(|&j| less(pivot, state[j])).unwrap(),
+        };
+        state.swap(i, j);
+
+        // state[i+1..dim] (降順) と state[dim..] (昇順) を合わせて昇順に並べる
+        state[i+1..dim].reverse();
+        state[i+1..].rotate_left(dim-i-1);
+
+        true
+    }
+
+    /// Rearranges `state` into the next permutation of numbers out of `0..n` in the lexicographic
+    /// order (or the previous one if `!forward`), or returns `false` if there is no such permutation.
+    ///
+    /// Unlike `shift`, `state` does not hold the unused numbers, since `array::Permutation`
+    /// has no room for them. So this takes `O(dim^2)` per step, which is fine for a small `K`.
+    pub(crate) fn shift_partial( state: &mut [usize], n: usize, forward: bool ) -> bool {
+        let dim = state.len();
+
+        // 取り替えられる一番右の位置を探し、それより右は残りの数で詰める
+        for i in (0..dim).rev() {
+            let (used, rest) = state.split_at(i);
+            let found = if forward {
+                (rest[0]+1..n).find(|x| !used.contains(x))
+            } else {
+                (0..rest[0]).rev().find(|x| !used.contains(x))
+            };
+
+            if let Some(x) = found {
+                state[i] = x;
+                for j in i+1..dim {
+                    let used = &state[..j];
+                    state[j] = if forward {
+                        (0..n).find(|x| !used.contains(x)).unwrap()
+                    } else {
+                        (0..n).rev().find(|x| !used.contains(x)).unwrap()
+                    };
+                }
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+
+pub(crate) mod sequence {
+    /// Rearranges `state` into the next sequence, or returns `false` if it is the last one.
+    pub(crate) fn next_state( state: &mut [usize], radices: &[usize] ) -> bool {
+        // 繰り上げ処理
+        for (x, &n) in state.iter_mut().zip(radices).rev() {
+            if *x + 1 < n {
+                *x += 1;
+                return true;
+            }
+            *x = 0;
+        }
+
+        false
+    }
+
+    /// Rearranges `state` into the previous sequence, or returns `false` if it is the first one.
+    pub(crate) fn prev_state( state: &mut [usize], radices: &[usize] ) -> bool {
+        // 繰り下げ処理
+        for (x, &n) in state.iter_mut().zip(radices).rev() {
+            if *x > 0 {
+                *x -= 1;
+                return true;
+            }
+            *x = n - 1;
+        }
+
+        false
+    }
+}
+
+
+pub(crate) mod combination {
+    /// Rearranges `state` into the next combination, or returns `false` if it is the last one.
+    pub(crate) fn next_state( state: &mut [usize], n: usize ) -> bool {
+        let dim = state.len();
+
+        // 1 を足せる一番右の位置を探し、それより右は詰めて並べる
+        match (0..dim).rev().find(|&i| state[i] < n - dim + i) {
+            Some(i) => {
+                state[i] += 1;
+                let x = state[i];
+                for (k, y) in state[i+1..].iter_mut().enumerate() {
+                    *y = x + 1 + k;
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Rearranges `state` into the previous combination, or returns `false` if it is the first one.
+    pub(crate) fn prev_state( state: &mut [usize], n: usize ) -> bool {
+        let dim = state.len();
+
+        // 1 を引ける一番右の位置を探し、それより右は最大にする
+        match (0..dim).rev().find(|&i| state[i] > if i == 0 { 0 } else { state[i-1] + 1 }) {
+            Some(i) => {
+                state[i] -= 1;
+                for (j, x) in state.iter_mut().enumerate().skip(i+1) {
+                    *x = n - dim + j;
+                }
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+
+pub(crate) mod multichoose {
+    /// Rearranges `state` into the next multiset, or returns `false` if it is the last one.
+    pub(crate) fn next_state( state: &mut [usize], n: usize ) -> bool {
+        let dim = state.len();
+
+        // 1 を足せる一番右の位置を探し、それより右はその数でそろえる
+        match (0..dim).rev().find(|&i| state[i] + 1 < n) {
+            Some(i) => {
+                state[i] += 1;
+                let x = state[i];
+                for y in state[i+1..].iter_mut() {
+                    *y = x;
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Rearranges `state` into the previous multiset, or returns `false` if it is the first one.
+    pub(crate) fn prev_state( state: &mut [usize], n: usize ) -> bool {
+        let dim = state.len();
+
+        // 1 を引ける一番右の位置を探し、それより右は最大にする
+        match (0..dim).rev().find(|&i| state[i] > if i == 0 { 0 } else { state[i-1] }) {
+            Some(i) => {
+                state[i] -= 1;
+                for x in state[i+1..].iter_mut() {
+                    *x = n - 1;
+                }
+                true
+            },
+            None => false,
+        }
+    }
+}